@@ -0,0 +1,94 @@
+//! Compares `unrolled::List` with `LinkedList` and `VecDeque`, on iteration
+//! and on inserting in the middle.
+//!
+//! Run with `cargo bench --bench unrolled`.
+
+use std::{
+    collections::VecDeque,
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use lists::{linked_list::LinkedList, unrolled};
+
+const LEN: u64 = 100_000;
+const MIDDLE_LEN: u64 = 10_000;
+const INSERTS: u64 = 64;
+
+/// Runs `f` repeatedly for about a second, and prints the mean time per run.
+fn bench(name: &str, mut f: impl FnMut()) {
+    // Warm up the caches and the branch predictor.
+    for _ in 0..3 {
+        f();
+    }
+
+    let start = Instant::now();
+    let mut runs = 0;
+    while start.elapsed() < Duration::from_secs(1) {
+        f();
+        runs += 1;
+    }
+    println!("{:<40} {:>12.1?}/run", name, start.elapsed() / runs);
+}
+
+fn main() {
+    let linked: LinkedList<u64> = (0..LEN).collect();
+    let unrolled: unrolled::List<u64> = (0..LEN).collect();
+    let deque: VecDeque<u64> = (0..LEN).collect();
+
+    bench("iterate/LinkedList", || {
+        black_box(black_box(&linked).iter().sum::<u64>());
+    });
+    bench("iterate/unrolled::List", || {
+        black_box(black_box(&unrolled).iter().sum::<u64>());
+    });
+    bench("iterate/VecDeque", || {
+        black_box(black_box(&deque).iter().sum::<u64>());
+    });
+
+    // Each run walks to the middle, inserts a few elements there, and removes
+    // them again, so the collections keep the same length between runs.
+    let mut linked: LinkedList<u64> = (0..MIDDLE_LEN).collect();
+    let mut unrolled: unrolled::List<u64> = (0..MIDDLE_LEN).collect();
+    let mut deque: VecDeque<u64> = (0..MIDDLE_LEN).collect();
+
+    bench("insert_middle/LinkedList", || {
+        let mut cursor = linked.cursor_mut();
+        for _ in 0..MIDDLE_LEN / 2 {
+            cursor.move_next();
+        }
+        for i in 0..INSERTS {
+            cursor.splice_before(Some(black_box(i)).into_iter().collect());
+        }
+        for _ in 0..INSERTS {
+            cursor.move_prev();
+            black_box(cursor.remove_current());
+        }
+    });
+    bench("insert_middle/unrolled::List", || {
+        let mut cursor = unrolled.cursor_mut();
+        for _ in 0..MIDDLE_LEN / 2 {
+            cursor.move_next();
+        }
+        for i in 0..INSERTS {
+            cursor.insert_before(black_box(i));
+        }
+        for _ in 0..INSERTS {
+            cursor.move_prev();
+            black_box(cursor.remove_current());
+        }
+    });
+    bench("insert_middle/VecDeque", || {
+        let middle = (MIDDLE_LEN / 2) as usize;
+        for i in 0..INSERTS {
+            deque.insert(middle + i as usize, black_box(i));
+        }
+        for _ in 0..INSERTS {
+            black_box(deque.remove(middle));
+        }
+    });
+
+    assert_eq!(linked.len() as u64, MIDDLE_LEN);
+    assert_eq!(unrolled.len() as u64, MIDDLE_LEN);
+    assert_eq!(deque.len() as u64, MIDDLE_LEN);
+}
@@ -0,0 +1,1053 @@
+//! A doubly linked list without any `unsafe`, storing its nodes in a [`Vec`]
+//! and linking them by their index in it.
+//!
+//! Inserting an element returns a generational [`Index`] handle to it, which
+//! can later be used to access, remove, or insert next to the element in O(1).
+//! Freed slots are reused, but each reuse bumps the generation of the slot,
+//! so stale handles are detected instead of silently pointing to another
+//! element. A slot whose generation runs out is retired rather than reused.
+//!
+//! The price of staying safe is paid by [`List::iter_mut`]: to hand out
+//! mutable references to nodes scattered across the [`Vec`], it keeps the
+//! slots not handed out yet as disjoint runs, and splits the run holding each
+//! node with [`slice::split_at_mut`], so each step is O(log len) rather than
+//! O(1).
+
+#![forbid(unsafe_code)]
+
+use core::{
+    fmt::Debug,
+    hash::Hash,
+    mem,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use std::collections::BTreeMap;
+
+/// Source of the ids telling lists apart, so that a handle never resolves in
+/// another list, even one whose slots have the same generations.
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+pub struct List<T> {
+    id: u64,
+    slots: Vec<Slot<T>>,
+    head: Option<u32>,
+    tail: Option<u32>,
+    // Head of the singly linked list of free slots.
+    free: Option<u32>,
+    len: usize,
+}
+
+struct Slot<T> {
+    generation: u32,
+    entry: Entry<T>,
+}
+
+enum Entry<T> {
+    Occupied(Node<T>),
+    Free { next_free: Option<u32> },
+}
+
+struct Node<T> {
+    prev: Option<u32>,
+    next: Option<u32>,
+    elem: T,
+}
+
+/// A handle to an element of a [`List`].
+///
+/// A handle only resolves in the list that returned it. Once its element
+/// is removed, or moved to another list by splitting or splicing, the handle
+/// becomes stale, and every operation using it returns `None` (or gives the
+/// inserted element back).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Index {
+    list: u64,
+    slot: u32,
+    generation: u32,
+}
+
+impl<T> List<T> {
+    /// Creates a new [`List`].
+    pub fn new() -> Self {
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            slots: Vec::new(),
+            head: None,
+            tail: None,
+            free: None,
+            len: 0,
+        }
+    }
+
+    /// Inserts an element at the beginning of the list, and returns its handle.
+    pub fn push_front(&mut self, elem: T) -> Index {
+        self.insert_between(elem, None, self.head)
+    }
+
+    /// Inserts an element at the back of the list, and returns its handle.
+    pub fn push_back(&mut self, elem: T) -> Index {
+        self.insert_between(elem, self.tail, None)
+    }
+
+    /// Removes an element from the beginning of the list, and returns it.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.map(|head| self.remove_slot(head))
+    }
+
+    /// Removes an element from the end of the list, and returns it.
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.tail.map(|tail| self.remove_slot(tail))
+    }
+
+    /// Returns a reference to the first element of the list.
+    pub fn front(&self) -> Option<&T> {
+        self.head.map(|head| &self.node(head).elem)
+    }
+
+    /// Returns a mutable reference to the first element of the list.
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.head.map(|head| &mut self.node_mut(head).elem)
+    }
+
+    /// Returns a reference to the last element of the list.
+    pub fn back(&self) -> Option<&T> {
+        self.tail.map(|tail| &self.node(tail).elem)
+    }
+
+    /// Returns a mutable reference to the last element of the list.
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.tail.map(|tail| &mut self.node_mut(tail).elem)
+    }
+
+    /// The length of the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clears the collection.
+    ///
+    /// Slots are kept for reuse, so handles to the removed elements stay stale.
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    /// Returns a reference to the element of `index`, or `None` if the handle is stale.
+    pub fn get(&self, index: Index) -> Option<&T> {
+        self.resolve(index).map(|slot| &self.node(slot).elem)
+    }
+
+    /// Returns a mutable reference to the element of `index`, or `None` if the
+    /// handle is stale.
+    pub fn get_mut(&mut self, index: Index) -> Option<&mut T> {
+        self.resolve(index)
+            .map(|slot| &mut self.node_mut(slot).elem)
+    }
+
+    /// Removes the element of `index` in O(1), and returns it, or `None` if the
+    /// handle is stale.
+    pub fn remove(&mut self, index: Index) -> Option<T> {
+        self.resolve(index).map(|slot| self.remove_slot(slot))
+    }
+
+    /// Inserts an element after the element of `index` in O(1), and returns its
+    /// handle. Gives the element back if the handle is stale.
+    pub fn insert_after(&mut self, index: Index, elem: T) -> Result<Index, T> {
+        match self.resolve(index) {
+            Some(slot) => Ok(self.insert_between(elem, Some(slot), self.node(slot).next)),
+            None => Err(elem),
+        }
+    }
+
+    /// Inserts an element before the element of `index` in O(1), and returns its
+    /// handle. Gives the element back if the handle is stale.
+    pub fn insert_before(&mut self, index: Index, elem: T) -> Result<Index, T> {
+        match self.resolve(index) {
+            Some(slot) => Ok(self.insert_between(elem, self.node(slot).prev, Some(slot))),
+            None => Err(elem),
+        }
+    }
+
+    /// Returns the slot of `index`, if the handle is not stale.
+    fn resolve(&self, index: Index) -> Option<u32> {
+        if index.list != self.id {
+            return None;
+        }
+        match self.slots.get(index.slot as usize) {
+            Some(Slot {
+                generation,
+                entry: Entry::Occupied(_),
+            }) if *generation == index.generation => Some(index.slot),
+            _ => None,
+        }
+    }
+
+    fn node(&self, slot: u32) -> &Node<T> {
+        match &self.slots[slot as usize].entry {
+            Entry::Occupied(node) => node,
+            Entry::Free { .. } => unreachable!("linked slot {} is free", slot),
+        }
+    }
+
+    fn node_mut(&mut self, slot: u32) -> &mut Node<T> {
+        match &mut self.slots[slot as usize].entry {
+            Entry::Occupied(node) => node,
+            Entry::Free { .. } => unreachable!("linked slot {} is free", slot),
+        }
+    }
+
+    /// Stores a new node between `prev` and `next`, which must be adjacent.
+    fn insert_between(&mut self, elem: T, prev: Option<u32>, next: Option<u32>) -> Index {
+        let node = Node { prev, next, elem };
+        let slot = match self.free {
+            Some(slot) => {
+                // Reuse a free slot, whose generation was bumped when freed.
+                let entry =
+                    mem::replace(&mut self.slots[slot as usize].entry, Entry::Occupied(node));
+                if let Entry::Free { next_free } = entry {
+                    self.free = next_free;
+                }
+                slot
+            }
+            None => {
+                let slot = u32::try_from(self.slots.len()).expect("too many slots");
+                self.slots.push(Slot {
+                    generation: 0,
+                    entry: Entry::Occupied(node),
+                });
+                slot
+            }
+        };
+
+        match prev {
+            Some(prev) => self.node_mut(prev).next = Some(slot),
+            None => self.head = Some(slot),
+        }
+        match next {
+            Some(next) => self.node_mut(next).prev = Some(slot),
+            None => self.tail = Some(slot),
+        }
+        self.len += 1;
+
+        self.index_of(slot)
+    }
+
+    fn index_of(&self, slot: u32) -> Index {
+        Index {
+            list: self.id,
+            slot,
+            generation: self.slots[slot as usize].generation,
+        }
+    }
+
+    /// Unlinks and frees the node in `slot`, and returns its element.
+    fn remove_slot(&mut self, slot: u32) -> T {
+        let freed = &mut self.slots[slot as usize];
+        // A slot out of generations is never reused, since a wrapped one
+        // would make the oldest stale handles valid again.
+        let next_free = match freed.generation.checked_add(1) {
+            Some(generation) => {
+                freed.generation = generation;
+                self.free.replace(slot)
+            }
+            None => None,
+        };
+        let entry = mem::replace(&mut freed.entry, Entry::Free { next_free });
+
+        let Entry::Occupied(node) = entry else {
+            unreachable!("linked slot {} is free", slot);
+        };
+        match node.prev {
+            Some(prev) => self.node_mut(prev).next = node.next,
+            None => self.head = node.next,
+        }
+        match node.next {
+            Some(next) => self.node_mut(next).prev = node.prev,
+            None => self.tail = node.prev,
+        }
+        self.len -= 1;
+
+        node.elem
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        // Slots are compacted, so handles of `self` are meaningless for the clone.
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Debug> Debug for List<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: PartialOrd> PartialOrd for List<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for List<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+pub struct Iter<'a, T> {
+    list: &'a List<T>,
+    front: Option<u32>,
+    back: Option<u32>,
+    len: usize,
+}
+
+impl<T> List<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            list: self,
+            front: self.head,
+            back: self.tail,
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|front| {
+                let node = self.list.node(front);
+                self.len -= 1;
+                self.front = node.next;
+                &node.elem
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|back| {
+                let node = self.list.node(back);
+                self.len -= 1;
+                self.back = node.prev;
+                &node.elem
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+pub struct IterMut<'a, T> {
+    // The slots not handed out yet, as disjoint runs keyed by their first slot.
+    runs: BTreeMap<u32, &'a mut [Slot<T>]>,
+    front: Option<u32>,
+    back: Option<u32>,
+    len: usize,
+}
+
+impl<T> List<T> {
+    /// Returns a mutable iterator over the elements of the list.
+    ///
+    /// Without `unsafe`, each step splits the node off the borrowed slots, so
+    /// it is O(log len).
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            runs: BTreeMap::from([(0, self.slots.as_mut_slice())]),
+            front: self.head,
+            back: self.tail,
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, T> IterMut<'a, T> {
+    /// Splits the node in `slot` off the run holding it, leaving the slots
+    /// around it as runs of their own.
+    fn take(&mut self, slot: u32) -> &'a mut Node<T> {
+        let start = self
+            .runs
+            .range(..=slot)
+            .next_back()
+            .map(|(&start, _)| start);
+        let run = start.and_then(|start| self.runs.remove(&start));
+        let (Some(start), Some(run)) = (start, run) else {
+            unreachable!("slot {} was handed out", slot);
+        };
+        let (before, rest) = run.split_at_mut((slot - start) as usize);
+        let (this, after) = rest.split_first_mut().expect("slot out of bounds");
+        if !before.is_empty() {
+            self.runs.insert(start, before);
+        }
+        if !after.is_empty() {
+            self.runs.insert(slot + 1, after);
+        }
+        match &mut this.entry {
+            Entry::Occupied(node) => node,
+            Entry::Free { .. } => unreachable!("linked slot {} is free", slot),
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|front| {
+                let node = self.take(front);
+                self.len -= 1;
+                self.front = node.next;
+                &mut node.elem
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|back| {
+                let node = self.take(back);
+                self.len -= 1;
+                self.back = node.prev;
+                &mut node.elem
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+pub struct IntoIter<T> {
+    list: List<T>,
+}
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+pub struct CursorMut<'a, T> {
+    cur: Option<u32>,
+    list: &'a mut List<T>,
+    index: Option<usize>,
+}
+
+impl<T> List<T> {
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            cur: None,
+            list: self,
+            index: None,
+        }
+    }
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    /// Retreive a current index of the cursor.
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    /// Retrieve the handle of the element at the cursor.
+    pub fn handle(&self) -> Option<Index> {
+        self.cur.map(|slot| self.list.index_of(slot))
+    }
+
+    /// Move the cursor to the next position.
+    pub fn move_next(&mut self) {
+        if let Some(cur) = self.cur {
+            self.cur = self.list.node(cur).next;
+            if self.cur.is_some() {
+                self.index = Some(self.index.unwrap() + 1);
+            } else {
+                // We just moved into the ghost element.
+                self.index = None;
+            }
+        } else if !self.list.is_empty() {
+            // We're at the ghost element, and there is a head element.
+            self.cur = self.list.head;
+            self.index = Some(0);
+        } else {
+            // The list is empty, do nothing.
+        }
+    }
+
+    /// Move the cursor to the previous position.
+    pub fn move_prev(&mut self) {
+        if let Some(cur) = self.cur {
+            self.cur = self.list.node(cur).prev;
+            if self.cur.is_some() {
+                self.index = Some(self.index.unwrap() - 1);
+            } else {
+                // We just moved into the ghost element.
+                self.index = None;
+            }
+        } else if !self.list.is_empty() {
+            // We're at the ghost element, and there is a tail element.
+            self.cur = self.list.tail;
+            self.index = Some(self.list.len - 1);
+        } else {
+            // The list is empty, do nothing.
+        }
+    }
+
+    /// Retrieve an element at the cursor.
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.cur.map(|cur| &mut self.list.node_mut(cur).elem)
+    }
+
+    /// Retrieve the element next to the cursor.
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        self.cur
+            .map_or(self.list.head, |cur| self.list.node(cur).next)
+            .map(|next| &mut self.list.node_mut(next).elem)
+    }
+
+    /// Retrieve the element before the cursor.
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        self.cur
+            .map_or(self.list.tail, |cur| self.list.node(cur).prev)
+            .map(|prev| &mut self.list.node_mut(prev).elem)
+    }
+
+    /// Creates a new list by splitting the list before the cursor, returning the newly created list.
+    /// The cursor will remain at the original list.
+    ///
+    /// The split off elements are moved to the slots of the new list, so this
+    /// is O(index), and their handles become stale.
+    pub fn split_before(&mut self) -> List<T> {
+        // At the ghost, this runs until the original list is empty. The
+        // elements are popped even then, rather than taking the whole list,
+        // so that the slots left behind are freed and their handles go stale.
+        let mut splitted_list = List::new();
+        while self.list.head != self.cur {
+            splitted_list.push_back(self.list.pop_front().unwrap());
+        }
+        if self.cur.is_some() {
+            self.index = Some(0);
+        }
+        splitted_list
+    }
+
+    /// Creates a new list by splitting the list after the cursor, returning the newly created list.
+    /// The cursor will remain at the original list.
+    ///
+    /// The split off elements are moved to the slots of the new list, so this
+    /// is O(len - index), and their handles become stale.
+    pub fn split_after(&mut self) -> List<T> {
+        // At the ghost, this runs until the original list is empty.
+        let mut splitted_list = List::new();
+        while self.list.tail != self.cur {
+            splitted_list.push_front(self.list.pop_back().unwrap());
+        }
+        splitted_list
+    }
+
+    /// Inserts the given list before the cursor.
+    ///
+    /// The elements are moved to the slots of this list, so this is
+    /// O(input.len()), and handles of the input list become stale.
+    pub fn splice_before(&mut self, input: List<T>) {
+        if let Some(cur) = self.cur {
+            self.index = Some(self.index.unwrap() + input.len());
+            for elem in input {
+                let prev = self.list.node(cur).prev;
+                self.list.insert_between(elem, prev, Some(cur));
+            }
+        } else {
+            // Append the input list at the back of current list.
+            // Cursor remains at the ghost.
+            self.list.extend(input);
+        }
+    }
+
+    /// Inserts the given list after the cursor.
+    ///
+    /// The elements are moved to the slots of this list, so this is
+    /// O(input.len()), and handles of the input list become stale.
+    pub fn splice_after(&mut self, input: List<T>) {
+        if let Some(cur) = self.cur {
+            for elem in input.into_iter().rev() {
+                let next = self.list.node(cur).next;
+                self.list.insert_between(elem, Some(cur), next);
+            }
+        } else {
+            // Append the input list at the start of current list.
+            // Cursor remains at the ghost.
+            for elem in input.into_iter().rev() {
+                self.list.push_front(elem);
+            }
+        }
+    }
+
+    /// Removes the current element and returns it.
+    /// The cursor will be moved to the next element.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let cur = self.cur?;
+        self.cur = self.list.node(cur).next;
+        if self.cur.is_none() {
+            self.index = None;
+        }
+        Some(self.list.remove_slot(cur))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Index, List};
+
+    fn generate_test() -> List<i32> {
+        list_from(&[0, 1, 2, 3, 4, 5, 6])
+    }
+
+    fn list_from<T: Clone>(v: &[T]) -> List<T> {
+        v.iter().map(|x| (*x).clone()).collect()
+    }
+
+    fn check_links<T: Eq + std::fmt::Debug>(list: &List<T>) {
+        let from_front: Vec<_> = list.iter().collect();
+        let from_back: Vec<_> = list.iter().rev().collect();
+        let re_reved: Vec<_> = from_back.into_iter().rev().collect();
+
+        assert_eq!(from_front, re_reved);
+    }
+
+    #[test]
+    fn basic() {
+        let mut m = List::new();
+        assert_eq!(m.pop_front(), None);
+        assert_eq!(m.pop_back(), None);
+        assert_eq!(m.pop_front(), None);
+        m.push_front(1);
+        assert_eq!(m.pop_front(), Some(1));
+        m.push_back(2);
+        m.push_back(3);
+        assert_eq!(m.len(), 2);
+        assert_eq!(m.pop_front(), Some(2));
+        assert_eq!(m.pop_front(), Some(3));
+        assert_eq!(m.len(), 0);
+        assert_eq!(m.pop_front(), None);
+        m.push_back(1);
+        m.push_back(3);
+        m.push_back(5);
+        m.push_back(7);
+        assert_eq!(m.pop_front(), Some(1));
+
+        let mut n = List::new();
+        n.push_front(2);
+        n.push_front(3);
+        {
+            assert_eq!(n.front().unwrap(), &3);
+            let x = n.front_mut().unwrap();
+            assert_eq!(*x, 3);
+            *x = 0;
+        }
+        {
+            assert_eq!(n.back().unwrap(), &2);
+            let y = n.back_mut().unwrap();
+            assert_eq!(*y, 2);
+            *y = 1;
+        }
+        assert_eq!(n.pop_front(), Some(0));
+        assert_eq!(n.pop_front(), Some(1));
+    }
+
+    #[test]
+    fn iterator_double_end() {
+        let mut n = List::new();
+        assert_eq!(n.iter().next(), None);
+        n.push_front(4);
+        n.push_front(5);
+        n.push_front(6);
+        let mut it = n.iter();
+        assert_eq!(it.size_hint(), (3, Some(3)));
+        assert_eq!(it.next().unwrap(), &6);
+        assert_eq!(it.size_hint(), (2, Some(2)));
+        assert_eq!(it.next_back().unwrap(), &4);
+        assert_eq!(it.size_hint(), (1, Some(1)));
+        assert_eq!(it.next_back().unwrap(), &5);
+        assert_eq!(it.next_back(), None);
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn iterator_mut_double_end() {
+        let mut n = generate_test();
+        n.pop_front();
+        n.push_front(10);
+        let mut it = n.iter_mut();
+        assert_eq!(it.size_hint(), (7, Some(7)));
+        assert_eq!(*it.next().unwrap(), 10);
+        *it.next_back().unwrap() = 60;
+        assert_eq!(*it.next_back().unwrap(), 5);
+        for elem in it {
+            *elem *= 10;
+        }
+        assert_eq!(
+            n.into_iter().collect::<Vec<_>>(),
+            [10, 10, 20, 30, 40, 5, 60]
+        );
+    }
+
+    #[test]
+    fn iter_mut_scattered() {
+        // Link the nodes out of slot order, with a free slot among them.
+        let mut list = List::new();
+        let two = list.push_back(2);
+        let four = list.push_back(4);
+        list.push_front(1);
+        let three = list.insert_after(two, 3).unwrap();
+        list.remove(four);
+        list.insert_before(three, 20).unwrap();
+        list.push_back(5);
+
+        let mut it = list.iter_mut();
+        *it.next().unwrap() *= 10;
+        *it.next_back().unwrap() *= 10;
+        *it.next().unwrap() *= 10;
+        *it.next_back().unwrap() *= 10;
+        *it.next().unwrap() *= 10;
+        assert!(it.next().is_none());
+        assert!(it.next_back().is_none());
+        check_links(&list);
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            [10, 20, 200, 30, 50]
+        );
+    }
+
+    #[test]
+    fn traits() {
+        let n = generate_test();
+        let mut m = n.clone();
+        assert_eq!(n, m);
+        assert!(n >= m);
+        m.push_back(7);
+        assert!(n != m);
+        assert!(n < m);
+        assert_eq!(format!("{:?}", n), "[0, 1, 2, 3, 4, 5, 6]");
+
+        let mut map = std::collections::HashMap::new();
+        map.insert(n.clone(), "n");
+        assert_eq!(map.get(&generate_test()), Some(&"n"));
+    }
+
+    #[test]
+    fn handles() {
+        let mut list = List::new();
+        let one = list.push_back(1);
+        let three = list.push_back(3);
+        let two = list.insert_after(one, 2).unwrap();
+        let zero = list.insert_before(one, 0).unwrap();
+        check_links(&list);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3]);
+        assert_eq!(list.get(zero), Some(&0));
+
+        *list.get_mut(three).unwrap() = 30;
+        assert_eq!(list.remove(two), Some(2));
+        check_links(&list);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [0, 1, 30]);
+
+        // The freed slot is reused, but the old handle stays stale.
+        let four = list.push_back(4);
+        assert_eq!(list.get(two), None);
+        assert_eq!(list.remove(two), None);
+        assert_eq!(list.insert_after(two, 5), Err(5));
+        assert_eq!(list.insert_before(two, 6), Err(6));
+        assert_eq!(list.get(four), Some(&4));
+
+        list.clear();
+        assert!(list.is_empty());
+        assert_eq!(list.get(zero), None);
+        assert_eq!(list.get(four), None);
+    }
+
+    #[test]
+    fn retired_slot() {
+        let mut list = List::new();
+        let old = list.push_back(1);
+        list.slots[old.slot as usize].generation = u32::MAX;
+        let last = Index {
+            generation: u32::MAX,
+            ..old
+        };
+        assert_eq!(list.remove(last), Some(1));
+
+        // The slot is out of generations, so it is not reused.
+        let new = list.push_back(2);
+        assert_ne!(new.slot, last.slot);
+        assert_eq!(list.get(old), None);
+        assert_eq!(list.get(last), None);
+        assert_eq!(list.get(new), Some(&2));
+        check_links(&list);
+    }
+
+    #[test]
+    fn ghost_split_handles() {
+        let mut list = List::new();
+        let one = list.push_back(1);
+        list.push_back(2);
+        let moved = list.cursor_mut().split_before();
+        list.push_back(3);
+        assert_eq!(moved.iter().copied().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(moved.get(one), None);
+        assert_eq!(list.get(one), None);
+
+        let mut cursor = list.cursor_mut();
+        cursor.move_next();
+        let three = cursor.handle().unwrap();
+        cursor.move_prev();
+        let moved = cursor.split_after();
+        list.push_back(4);
+        assert_eq!(moved.iter().copied().collect::<Vec<_>>(), [3]);
+        assert_eq!(moved.get(three), None);
+        assert_eq!(list.get(three), None);
+        check_links(&list);
+    }
+
+    #[test]
+    fn cursor_move_peek() {
+        let mut m: List<u32> = List::new();
+        m.extend([1, 2, 3, 4, 5, 6]);
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.peek_next(), Some(&mut 2));
+        assert_eq!(cursor.peek_prev(), None);
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&mut 1));
+        assert_eq!(cursor.peek_prev(), Some(&mut 6));
+        assert_eq!(cursor.index(), None);
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 2));
+        assert_eq!(cursor.peek_next(), Some(&mut 3));
+        assert_eq!(cursor.peek_prev(), Some(&mut 1));
+        assert_eq!(cursor.index(), Some(1));
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 6));
+        assert_eq!(cursor.peek_next(), None);
+        assert_eq!(cursor.peek_prev(), Some(&mut 5));
+        assert_eq!(cursor.index(), Some(5));
+        let handle = cursor.handle().unwrap();
+        assert_eq!(m.get(handle), Some(&6));
+    }
+
+    #[test]
+    fn cursor_mut_insert() {
+        let mut m: List<u32> = List::new();
+        m.extend([1, 2, 3, 4, 5, 6]);
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.splice_before(Some(7).into_iter().collect());
+        cursor.splice_after(Some(8).into_iter().collect());
+        check_links(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[7, 1, 8, 2, 3, 4, 5, 6]
+        );
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.move_prev();
+        cursor.splice_before(Some(9).into_iter().collect());
+        cursor.splice_after(Some(10).into_iter().collect());
+        check_links(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[10, 7, 1, 8, 2, 3, 4, 5, 6, 9]
+        );
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current(), None);
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(7));
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current(), Some(9));
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(10));
+        check_links(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[1, 8, 2, 3, 4, 5, 6]
+        );
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        let mut p: List<u32> = List::new();
+        p.extend([100, 101, 102, 103]);
+        let mut q: List<u32> = List::new();
+        q.extend([200, 201, 202, 203]);
+        cursor.splice_after(p);
+        cursor.splice_before(q);
+        assert_eq!(cursor.index(), Some(4));
+        check_links(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[200, 201, 202, 203, 1, 100, 101, 102, 103, 8, 2, 3, 4, 5, 6]
+        );
+        assert_eq!(m.len(), 15);
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.move_prev();
+        let tmp = cursor.split_before();
+        assert_eq!(m.len(), 0);
+        assert_eq!(m.into_iter().collect::<Vec<_>>(), &[]);
+        m = tmp;
+        assert_eq!(m.len(), 15);
+        let mut cursor = m.cursor_mut();
+        for _ in 0..7 {
+            cursor.move_next();
+        }
+        let tmp = cursor.split_after();
+        assert_eq!(tmp.len(), 8);
+        assert_eq!(m.len(), 7);
+        assert_eq!(
+            tmp.into_iter().collect::<Vec<_>>(),
+            &[102, 103, 8, 2, 3, 4, 5, 6]
+        );
+        check_links(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[200, 201, 202, 203, 1, 100, 101]
+        );
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        cursor.move_next();
+        let tmp = cursor.split_before();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(tmp.into_iter().collect::<Vec<_>>(), &[200, 201]);
+        check_links(&m);
+        assert_eq!(m.len(), 5);
+    }
+}
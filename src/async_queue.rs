@@ -0,0 +1,556 @@
+//! An async queue that does not depend on any runtime.
+//!
+//! Tasks waiting for an element register their [`Waker`] in a waiter list, and
+//! every pushed element wakes the longest waiting one. Each future keeps a
+//! handle to its node in that list, so registering, waking and cancelling are
+//! all O(1).
+
+use std::{
+    future::Future,
+    pin::Pin,
+    ptr::NonNull,
+    sync::{Mutex, MutexGuard},
+    task::{Context, Poll, Waker},
+};
+
+use crate::linked_list::{LinkedList, Node};
+
+/// A FIFO queue whose elements can be awaited.
+pub struct AsyncQueue<T> {
+    inner: Mutex<Inner<T>>,
+}
+
+struct Inner<T> {
+    items: LinkedList<T>,
+    // Tasks waiting for an element, the longest waiting first.
+    waiters: LinkedList<Waiter>,
+    // Tasks woken up since they last polled. Their nodes are moved here
+    // rather than freed, so that the tasks' handles to them stay valid.
+    woken: LinkedList<Waiter>,
+    closed: bool,
+}
+
+struct Waiter {
+    waker: Waker,
+    // Whether the node is in `woken` rather than `waiters`.
+    woken: bool,
+}
+
+/// The node of a registered task, in either `waiters` or `woken`.
+///
+/// It is owned by the task's future, which unregisters it when done.
+struct Handle(NonNull<Node<Waiter>>);
+
+// The node is only accessed with the queue locked.
+unsafe impl Send for Handle {}
+unsafe impl Sync for Handle {}
+
+/// Future returned by [`AsyncQueue::pop`].
+///
+/// Dropping it before it completes cancels the pop, and passes any wake-up it
+/// received on to the next waiting task.
+pub struct Pop<'a, T> {
+    queue: &'a AsyncQueue<T>,
+    handle: Option<Handle>,
+}
+
+/// A stream of the elements popped from an [`AsyncQueue`], returned by
+/// [`AsyncQueue::stream`].
+pub struct PopStream<'a, T> {
+    queue: &'a AsyncQueue<T>,
+    handle: Option<Handle>,
+}
+
+impl<T> AsyncQueue<T> {
+    /// Creates an empty [`AsyncQueue`].
+    pub fn new() -> Self {
+        AsyncQueue {
+            inner: Mutex::new(Inner {
+                items: LinkedList::new(),
+                waiters: LinkedList::new(),
+                woken: LinkedList::new(),
+                closed: false,
+            }),
+        }
+    }
+
+    /// Inserts an element at the back of the queue, waking up a waiting task.
+    ///
+    /// Fails, giving the element back, if the queue was closed.
+    pub fn push(&self, elem: T) -> Result<(), T> {
+        let mut inner = self.lock();
+        if inner.closed {
+            return Err(elem);
+        }
+
+        inner.items.push_back(elem);
+        let waker = inner.wake_one();
+        drop(inner);
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        Ok(())
+    }
+
+    /// Removes an element from the front of the queue, waiting for one to be
+    /// pushed if the queue is empty.
+    ///
+    /// Resolves to `None` once the queue is closed and empty.
+    pub fn pop(&self) -> Pop<'_, T> {
+        Pop {
+            queue: self,
+            handle: None,
+        }
+    }
+
+    /// Removes an element from the front of the queue if there is any, without waiting.
+    pub fn try_pop(&self) -> Option<T> {
+        self.lock().items.pop_front()
+    }
+
+    /// Returns a stream popping the elements of the queue until it is closed and empty.
+    pub fn stream(&self) -> PopStream<'_, T> {
+        PopStream {
+            queue: self,
+            handle: None,
+        }
+    }
+
+    /// Closes the queue, waking up every waiting task.
+    ///
+    /// Elements pushed before closing can still be popped, but pushing fails.
+    pub fn close(&self) {
+        let mut inner = self.lock();
+        inner.closed = true;
+        let wakers: Vec<Waker> = std::iter::from_fn(|| inner.wake_one()).collect();
+        drop(inner);
+
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Returns `true` if the queue was closed.
+    pub fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+
+    /// The number of elements in the queue.
+    pub fn len(&self) -> usize {
+        self.lock().items.len()
+    }
+
+    /// Returns `true` if the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.lock().items.is_empty()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner<T>> {
+        self.inner.lock().unwrap()
+    }
+
+    /// Pops an element for the task registered with `handle`, registering it
+    /// if it has to wait.
+    fn poll_pop(&self, handle: &mut Option<Handle>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut inner = self.lock();
+
+        if let Some(elem) = inner.items.pop_front() {
+            if let Some(handle) = handle.take() {
+                unsafe { inner.unregister(handle) };
+            }
+            return Poll::Ready(Some(elem));
+        }
+
+        if inner.closed {
+            if let Some(handle) = handle.take() {
+                unsafe { inner.unregister(handle) };
+            }
+            return Poll::Ready(None);
+        }
+
+        match handle {
+            Some(handle) => unsafe { inner.update(handle, cx.waker()) },
+            None => {
+                let node = inner.waiters.push_back_node(Waiter {
+                    waker: cx.waker().clone(),
+                    woken: false,
+                });
+                *handle = Some(Handle(node));
+            }
+        }
+        Poll::Pending
+    }
+
+    /// Cancels the pop of the task registered with `handle`.
+    fn cancel(&self, handle: Option<Handle>) {
+        let Some(handle) = handle else {
+            return;
+        };
+
+        let mut inner = self.lock();
+        if unsafe { inner.unregister(handle) } && !inner.items.is_empty() {
+            // We were woken up for an element we will never take, so wake up
+            // someone else instead.
+            let waker = inner.wake_one();
+            drop(inner);
+
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl<T> Inner<T> {
+    /// Moves the longest waiting task to `woken`, and returns its waker.
+    fn wake_one(&mut self) -> Option<Waker> {
+        let node = self.waiters.front_node()?;
+        unsafe {
+            self.waiters.move_node_to_back_of(node, &mut self.woken);
+            let waiter = (*node.as_ptr()).elem_mut();
+            waiter.woken = true;
+            Some(waiter.waker.clone())
+        }
+    }
+
+    /// Replaces the waker of a registered task. If it was woken up but beaten
+    /// to the element, it goes back to the end of the line.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be registered with this queue.
+    unsafe fn update(&mut self, handle: &Handle, waker: &Waker) {
+        let node = handle.0;
+        if (*node.as_ptr()).elem().woken {
+            self.woken.move_node_to_back_of(node, &mut self.waiters);
+        }
+        let waiter = (*node.as_ptr()).elem_mut();
+        waiter.woken = false;
+        if !waiter.waker.will_wake(waker) {
+            waiter.waker = waker.clone();
+        }
+    }
+
+    /// Removes a registered task, returning `true` if it had been woken up.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be registered with this queue.
+    unsafe fn unregister(&mut self, handle: Handle) -> bool {
+        let node = handle.0;
+        if (*node.as_ptr()).elem().woken {
+            self.woken.remove_node(node);
+            true
+        } else {
+            self.waiters.remove_node(node);
+            false
+        }
+    }
+}
+
+impl<T> Default for AsyncQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T> Future for Pop<'a, T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        this.queue.poll_pop(&mut this.handle, cx)
+    }
+}
+
+impl<'a, T> Drop for Pop<'a, T> {
+    fn drop(&mut self) {
+        self.queue.cancel(self.handle.take());
+    }
+}
+
+impl<'a, T> PopStream<'a, T> {
+    /// Polls for the next element, like `Stream::poll_next`.
+    ///
+    /// Returns `Ready(None)` once the queue is closed and empty.
+    pub fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = self.get_mut();
+        this.queue.poll_pop(&mut this.handle, cx)
+    }
+}
+
+impl<'a, T> Drop for PopStream<'a, T> {
+    fn drop(&mut self) {
+        self.queue.cancel(self.handle.take());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        future::{poll_fn, Future},
+        pin::pin,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        task::{Context, Poll, Wake, Waker},
+        thread::{self, Thread},
+    };
+
+    use super::AsyncQueue;
+    use crate::util::test::assert_send_sync;
+
+    /// Runs a future to completion on the current thread.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        struct Unpark(Thread);
+
+        impl Wake for Unpark {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = pin!(future);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    /// A waker counting how many times it was woken up.
+    #[derive(Default)]
+    struct Count(AtomicUsize);
+
+    impl Wake for Count {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    impl Count {
+        fn get(&self) -> usize {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    #[test]
+    fn basics() {
+        let queue = AsyncQueue::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.try_pop(), None);
+
+        queue.push(1).unwrap();
+        queue.push(2).unwrap();
+        queue.push(3).unwrap();
+        assert_eq!(queue.len(), 3);
+
+        assert_eq!(block_on(queue.pop()), Some(1));
+        assert_eq!(block_on(queue.pop()), Some(2));
+        assert_eq!(queue.try_pop(), Some(3));
+        assert_eq!(queue.try_pop(), None);
+    }
+
+    #[test]
+    fn wakes_waiters_in_order() {
+        let queue = AsyncQueue::new();
+        let (count1, count2) = (Arc::new(Count::default()), Arc::new(Count::default()));
+        let (waker1, waker2) = (Waker::from(count1.clone()), Waker::from(count2.clone()));
+
+        let mut pop1 = pin!(queue.pop());
+        let mut pop2 = pin!(queue.pop());
+        assert!(pop1
+            .as_mut()
+            .poll(&mut Context::from_waker(&waker1))
+            .is_pending());
+        assert!(pop2
+            .as_mut()
+            .poll(&mut Context::from_waker(&waker2))
+            .is_pending());
+
+        queue.push(1).unwrap();
+        assert_eq!((count1.get(), count2.get()), (1, 0));
+        queue.push(2).unwrap();
+        assert_eq!((count1.get(), count2.get()), (1, 1));
+
+        let cx = &mut Context::from_waker(&waker1);
+        assert_eq!(pop1.as_mut().poll(cx), Poll::Ready(Some(1)));
+        let cx = &mut Context::from_waker(&waker2);
+        assert_eq!(pop2.as_mut().poll(cx), Poll::Ready(Some(2)));
+    }
+
+    #[test]
+    fn cancel() {
+        let queue = AsyncQueue::new();
+        let (count1, count2) = (Arc::new(Count::default()), Arc::new(Count::default()));
+        let (waker1, waker2) = (Waker::from(count1.clone()), Waker::from(count2.clone()));
+
+        // A cancelled pop is not woken up anymore.
+        {
+            let mut pop = pin!(queue.pop());
+            assert!(pop
+                .as_mut()
+                .poll(&mut Context::from_waker(&waker1))
+                .is_pending());
+        }
+        queue.push(1).unwrap();
+        assert_eq!(count1.get(), 0);
+        assert_eq!(queue.try_pop(), Some(1));
+
+        // A pop cancelled after being woken up passes the wake-up on.
+        let mut pop2 = pin!(queue.pop());
+        {
+            let mut pop1 = pin!(queue.pop());
+            assert!(pop1
+                .as_mut()
+                .poll(&mut Context::from_waker(&waker1))
+                .is_pending());
+            assert!(pop2
+                .as_mut()
+                .poll(&mut Context::from_waker(&waker2))
+                .is_pending());
+            queue.push(2).unwrap();
+            assert_eq!((count1.get(), count2.get()), (1, 0));
+        }
+        assert_eq!(count2.get(), 1);
+        let cx = &mut Context::from_waker(&waker2);
+        assert_eq!(pop2.as_mut().poll(cx), Poll::Ready(Some(2)));
+    }
+
+    #[test]
+    fn many_waiters() {
+        let queue = AsyncQueue::new();
+        let counts: Vec<_> = (0..100).map(|_| Arc::new(Count::default())).collect();
+        let wakers: Vec<_> = counts
+            .iter()
+            .map(|count| Waker::from(count.clone()))
+            .collect();
+        let mut pops: Vec<_> = (0..100).map(|_| Some(Box::pin(queue.pop()))).collect();
+        for (pop, waker) in pops.iter_mut().zip(&wakers) {
+            let cx = &mut Context::from_waker(waker);
+            assert!(pop.as_mut().unwrap().as_mut().poll(cx).is_pending());
+        }
+
+        // Cancel every other pop, then wake up the others in order.
+        for pop in pops.iter_mut().step_by(2) {
+            *pop = None;
+        }
+        for i in 0..50 {
+            queue.push(i).unwrap();
+        }
+        for (i, count) in counts.iter().enumerate() {
+            assert_eq!(count.get(), i % 2);
+        }
+
+        // Woken pops beaten to their element go back in line, in the order
+        // they poll again.
+        while queue.try_pop().is_some() {}
+        for i in [3, 1] {
+            let cx = &mut Context::from_waker(&wakers[i]);
+            assert!(pops[i].as_mut().unwrap().as_mut().poll(cx).is_pending());
+        }
+        queue.push(50).unwrap();
+        assert_eq!((counts[1].get(), counts[3].get()), (1, 2));
+        let cx = &mut Context::from_waker(&wakers[3]);
+        assert_eq!(
+            pops[3].as_mut().unwrap().as_mut().poll(cx),
+            Poll::Ready(Some(50))
+        );
+    }
+
+    #[test]
+    fn close() {
+        let queue = AsyncQueue::new();
+        let count = Arc::new(Count::default());
+        let waker = Waker::from(count.clone());
+
+        let mut pop = pin!(queue.pop());
+        assert!(pop
+            .as_mut()
+            .poll(&mut Context::from_waker(&waker))
+            .is_pending());
+
+        queue.close();
+        assert!(queue.is_closed());
+        assert_eq!(count.get(), 1);
+        assert_eq!(
+            pop.poll(&mut Context::from_waker(&waker)),
+            Poll::Ready(None)
+        );
+        assert_eq!(queue.push(1), Err(1));
+
+        // Elements pushed before closing can still be popped.
+        let queue = AsyncQueue::new();
+        queue.push(1).unwrap();
+        queue.close();
+        assert_eq!(block_on(queue.pop()), Some(1));
+        assert_eq!(block_on(queue.pop()), None);
+    }
+
+    #[test]
+    fn stream() {
+        let queue = Arc::new(AsyncQueue::new());
+
+        let producer = {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                for i in 0..100 {
+                    queue.push(i).unwrap();
+                }
+                queue.close();
+            })
+        };
+
+        let mut stream = pin!(queue.stream());
+        let mut received = Vec::new();
+        while let Some(elem) = block_on(poll_fn(|cx| stream.as_mut().poll_next(cx))) {
+            received.push(elem);
+        }
+        producer.join().unwrap();
+
+        assert_eq!(received, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn many_tasks() {
+        const CONSUMERS: usize = 4;
+
+        let queue = Arc::new(AsyncQueue::new());
+        let consumers: Vec<_> = (0..CONSUMERS)
+            .map(|_| {
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    let mut received = Vec::new();
+                    while let Some(elem) = block_on(queue.pop()) {
+                        received.push(elem);
+                    }
+                    received
+                })
+            })
+            .collect();
+
+        for i in 0..1_000 {
+            queue.push(i).unwrap();
+        }
+        queue.close();
+
+        let mut received: Vec<_> = consumers
+            .into_iter()
+            .flat_map(|consumer| consumer.join().unwrap())
+            .collect();
+        received.sort_unstable();
+        assert_eq!(received, (0..1_000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn send_sync() {
+        assert_send_sync::<AsyncQueue<i32>>();
+        assert_send_sync::<super::Pop<'_, i32>>();
+        assert_send_sync::<super::PopStream<'_, i32>>();
+    }
+}
@@ -0,0 +1,290 @@
+//! A least-recently-used cache, pairing a [`HashMap`] with a [`LinkedList`]
+//! ordered from the most to the least recently used entry.
+
+use std::{borrow::Borrow, collections::HashMap, fmt, hash::Hash, ptr::NonNull};
+
+use crate::linked_list::{LinkedList, Node};
+
+/// A cache holding at most `capacity` entries, evicting the least recently
+/// used one when it is full.
+pub struct LruCache<K, V> {
+    map: HashMap<K, NonNull<Node<(K, V)>>>,
+    list: LinkedList<(K, V)>,
+    capacity: usize,
+    on_evict: Option<Box<dyn FnMut(K, V)>>,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// Creates an empty [`LruCache`] holding at most `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            map: HashMap::with_capacity(capacity),
+            list: LinkedList::new(),
+            capacity,
+            on_evict: None,
+        }
+    }
+
+    /// Sets a callback, called with every entry evicted to make room.
+    ///
+    /// Entries removed explicitly, with [`LruCache::pop_lru`] or
+    /// [`LruCache::remove`], are not passed to the callback.
+    pub fn on_evict<F: FnMut(K, V) + 'static>(&mut self, f: F) {
+        self.on_evict = Some(Box::new(f));
+    }
+
+    /// Returns a reference to the value of `key`, marking it as the most recently used.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let node = *self.map.get(key)?;
+        unsafe {
+            self.list.move_node_to_front(node);
+            Some(&(*node.as_ptr()).elem().1)
+        }
+    }
+
+    /// Returns a mutable reference to the value of `key`, marking it as the
+    /// most recently used.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let node = *self.map.get(key)?;
+        unsafe {
+            self.list.move_node_to_front(node);
+            Some(&mut (*node.as_ptr()).elem_mut().1)
+        }
+    }
+
+    /// Returns a reference to the value of `key`, without marking it as used.
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let node = *self.map.get(key)?;
+        unsafe { Some(&(*node.as_ptr()).elem().1) }
+    }
+
+    /// Returns `true` if the cache holds `key`, without marking it as used.
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Inserts a value for `key`, marking it as the most recently used, and
+    /// returns the value it replaced.
+    ///
+    /// If the cache is full, the least recently used entry is evicted.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&node) = self.map.get(&key) {
+            unsafe {
+                self.list.move_node_to_front(node);
+                return Some(core::mem::replace(
+                    &mut (*node.as_ptr()).elem_mut().1,
+                    value,
+                ));
+            }
+        }
+
+        let node = self.list.push_front_node((key.clone(), value));
+        self.map.insert(key, node);
+        self.evict_to(self.capacity);
+        None
+    }
+
+    /// Removes `key` from the cache, and returns its value.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let node = self.map.remove(key)?;
+        unsafe { Some(self.list.remove_node(node).1) }
+    }
+
+    /// Removes the least recently used entry, and returns it.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let (key, value) = self.list.pop_back()?;
+        self.map.remove(&key);
+        Some((key, value))
+    }
+
+    /// Changes the capacity of the cache, evicting the least recently used
+    /// entries that do not fit anymore.
+    pub fn resize(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict_to(capacity);
+    }
+
+    /// The maximum number of entries in the cache.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of entries in the cache.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns `true` if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Returns an iterator over the entries, from the most to the least recently used.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> + ExactSizeIterator {
+        self.list.iter().map(|(key, value)| (key, value))
+    }
+
+    /// Evicts the least recently used entries until at most `len` remain.
+    fn evict_to(&mut self, len: usize) {
+        while self.list.len() > len {
+            let (key, value) = self.pop_lru().unwrap();
+            if let Some(on_evict) = &mut self.on_evict {
+                on_evict(key, value);
+            }
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for LruCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.list.iter().map(|(key, value)| (key, value)))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::LruCache;
+
+    /// Checks that the list is consistently linked, and that every key maps
+    /// to its own node.
+    fn check_links(cache: &LruCache<i32, &str>) {
+        let from_front: Vec<_> = cache.list.iter().collect();
+        let from_back: Vec<_> = cache.list.iter().rev().collect();
+        let re_reved: Vec<_> = from_back.into_iter().rev().collect();
+        assert_eq!(from_front, re_reved);
+
+        assert_eq!(cache.map.len(), cache.list.len());
+        for (key, node) in &cache.map {
+            assert_eq!(unsafe { &node.as_ref().elem().0 }, key);
+        }
+    }
+
+    fn keys(cache: &LruCache<i32, &str>) -> Vec<i32> {
+        cache.iter().map(|(key, _)| *key).collect()
+    }
+
+    #[test]
+    fn basics() {
+        let mut cache = LruCache::new(3);
+        assert!(cache.is_empty());
+        assert_eq!(cache.get(&1), None);
+
+        assert_eq!(cache.put(1, "one"), None);
+        check_links(&cache);
+        assert_eq!(cache.put(2, "two"), None);
+        check_links(&cache);
+        assert_eq!(cache.put(3, "three"), None);
+        check_links(&cache);
+        assert_eq!(keys(&cache), [3, 2, 1]);
+
+        assert_eq!(cache.get(&1), Some(&"one"));
+        check_links(&cache);
+        assert_eq!(keys(&cache), [1, 3, 2]);
+
+        assert_eq!(cache.peek(&2), Some(&"two"));
+        check_links(&cache);
+        assert_eq!(keys(&cache), [1, 3, 2]);
+
+        assert_eq!(cache.put(3, "THREE"), Some("three"));
+        check_links(&cache);
+        assert_eq!(keys(&cache), [3, 1, 2]);
+
+        *cache.get_mut(&2).unwrap() = "TWO";
+        check_links(&cache);
+        assert_eq!(keys(&cache), [2, 3, 1]);
+        assert_eq!(
+            format!("{:?}", cache),
+            r#"{2: "TWO", 3: "THREE", 1: "one"}"#
+        );
+    }
+
+    #[test]
+    fn eviction_order() {
+        let evicted = Rc::new(RefCell::new(Vec::new()));
+        let mut cache = LruCache::new(2);
+        {
+            let evicted = evicted.clone();
+            cache.on_evict(move |key, value| evicted.borrow_mut().push((key, value)));
+        }
+
+        cache.put(1, "one");
+        cache.put(2, "two");
+        cache.get(&1);
+        cache.put(3, "three");
+        check_links(&cache);
+        assert_eq!(*evicted.borrow(), [(2, "two")]);
+        assert!(!cache.contains(&2));
+
+        cache.put(4, "four");
+        check_links(&cache);
+        assert_eq!(*evicted.borrow(), [(2, "two"), (1, "one")]);
+        assert_eq!(keys(&cache), [4, 3]);
+
+        // Explicit removals are not evictions.
+        assert_eq!(cache.pop_lru(), Some((3, "three")));
+        check_links(&cache);
+        assert_eq!(cache.remove(&4), Some("four"));
+        check_links(&cache);
+        assert_eq!(cache.remove(&4), None);
+        assert_eq!(cache.pop_lru(), None);
+        assert_eq!(evicted.borrow().len(), 2);
+    }
+
+    #[test]
+    fn resize() {
+        let evicted = Rc::new(RefCell::new(Vec::new()));
+        let mut cache = LruCache::new(4);
+        {
+            let evicted = evicted.clone();
+            cache.on_evict(move |key, _| evicted.borrow_mut().push(key));
+        }
+
+        for key in 0..4 {
+            cache.put(key, "");
+        }
+        cache.get(&0);
+        cache.resize(2);
+        check_links(&cache);
+        assert_eq!(cache.capacity(), 2);
+        assert_eq!(*evicted.borrow(), [1, 2]);
+        assert_eq!(keys(&cache), [0, 3]);
+
+        cache.resize(3);
+        cache.put(4, "");
+        check_links(&cache);
+        assert_eq!(keys(&cache), [4, 0, 3]);
+        assert_eq!(evicted.borrow().len(), 2);
+
+        cache.resize(0);
+        check_links(&cache);
+        assert!(cache.is_empty());
+        cache.put(5, "");
+        check_links(&cache);
+        assert!(cache.is_empty());
+        assert_eq!(*evicted.borrow(), [1, 2, 3, 0, 4, 5]);
+    }
+}
@@ -0,0 +1,16 @@
+//! Lock-free collections that can be shared between threads.
+//!
+//! A node unlinked by one thread may still be read by another one that loaded
+//! it just before, so unlinked nodes are never freed directly. They are
+//! retired to a hazard pointer [`Domain`](hazard::Domain) instead, which frees
+//! them once no thread protects them anymore.
+
+mod hazard;
+mod queue;
+mod stack;
+
+#[cfg(test)]
+mod linearizability;
+
+pub use queue::Queue;
+pub use stack::Stack;
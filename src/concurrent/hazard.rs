@@ -0,0 +1,283 @@
+use std::{
+    collections::HashSet,
+    ptr,
+    sync::atomic::{fence, AtomicBool, AtomicPtr, AtomicUsize, Ordering},
+};
+
+/// Number of retired nodes after which a thread tries to reclaim them.
+const RECLAIM_THRESHOLD: usize = 64;
+
+/// A hazard pointer domain, owning the hazard pointers and the retired nodes
+/// of one collection.
+pub(crate) struct Domain {
+    records: AtomicPtr<Record>,
+    retired: AtomicPtr<Retired>,
+    retired_len: AtomicUsize,
+}
+
+/// A hazard pointer slot. Records are never freed before the domain is, so
+/// that the list of records can be traversed without protection.
+struct Record {
+    hazard: AtomicPtr<u8>,
+    active: AtomicBool,
+    next: *mut Record,
+}
+
+/// A node waiting to be freed, along with the function freeing it.
+struct Retired {
+    ptr: *mut u8,
+    free: unsafe fn(*mut u8),
+    next: *mut Retired,
+}
+
+/// A hazard pointer owned by a thread. Whatever it protects will not be freed
+/// until it is reset or dropped.
+pub(crate) struct Hazard<'a> {
+    record: &'a Record,
+}
+
+impl Domain {
+    /// Creates an empty [`Domain`].
+    pub(crate) fn new() -> Self {
+        Self {
+            records: AtomicPtr::new(ptr::null_mut()),
+            retired: AtomicPtr::new(ptr::null_mut()),
+            retired_len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Acquires a hazard pointer, reusing an inactive one if there is any.
+    pub(crate) fn hazard(&self) -> Hazard<'_> {
+        let mut cur = self.records.load(Ordering::Acquire);
+        while let Some(record) = unsafe { cur.as_ref() } {
+            if !record.active.load(Ordering::Relaxed)
+                && record
+                    .active
+                    .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            {
+                return Hazard { record };
+            }
+            cur = record.next;
+        }
+
+        // Every record is in use, so publish a new one.
+        let record = Box::into_raw(Box::new(Record {
+            hazard: AtomicPtr::new(ptr::null_mut()),
+            active: AtomicBool::new(true),
+            next: ptr::null_mut(),
+        }));
+        let mut head = self.records.load(Ordering::Relaxed);
+        loop {
+            unsafe { (*record).next = head };
+            match self.records.compare_exchange_weak(
+                head,
+                record,
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    return Hazard {
+                        record: unsafe { &*record },
+                    }
+                }
+                Err(new_head) => head = new_head,
+            }
+        }
+    }
+
+    /// Retires a node allocated with [`Box`], freeing it once no hazard
+    /// pointer protects it.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must come from [`Box::into_raw`], must already be unreachable
+    /// for threads that do not hold it yet, and must not be retired twice.
+    pub(crate) unsafe fn retire<T>(&self, ptr: *mut T) {
+        unsafe fn free<T>(ptr: *mut u8) {
+            drop(Box::from_raw(ptr as *mut T));
+        }
+
+        self.push_retired(Box::into_raw(Box::new(Retired {
+            ptr: ptr as *mut u8,
+            free: free::<T>,
+            next: ptr::null_mut(),
+        })));
+
+        if self.retired_len.fetch_add(1, Ordering::Relaxed) + 1 >= RECLAIM_THRESHOLD {
+            self.reclaim();
+        }
+    }
+
+    /// Frees every retired node that is not protected by a hazard pointer.
+    fn reclaim(&self) {
+        self.retired_len.store(0, Ordering::Relaxed);
+        let mut cur = self.retired.swap(ptr::null_mut(), Ordering::Acquire);
+
+        // Pairs with the fence in `Hazard::protect`: either we see the hazard
+        // pointer, or the protecting thread sees the node already unlinked.
+        fence(Ordering::SeqCst);
+
+        let mut protected = HashSet::new();
+        let mut record = self.records.load(Ordering::Acquire);
+        while let Some(r) = unsafe { record.as_ref() } {
+            let hazard = r.hazard.load(Ordering::Acquire);
+            if !hazard.is_null() {
+                protected.insert(hazard);
+            }
+            record = r.next;
+        }
+
+        while !cur.is_null() {
+            unsafe {
+                let next = (*cur).next;
+                if protected.contains(&(*cur).ptr) {
+                    // Still in use, try again next time.
+                    self.push_retired(cur);
+                    self.retired_len.fetch_add(1, Ordering::Relaxed);
+                } else {
+                    let retired = Box::from_raw(cur);
+                    (retired.free)(retired.ptr);
+                }
+                cur = next;
+            }
+        }
+    }
+
+    fn push_retired(&self, retired: *mut Retired) {
+        let mut head = self.retired.load(Ordering::Relaxed);
+        loop {
+            unsafe { (*retired).next = head };
+            match self.retired.compare_exchange_weak(
+                head,
+                retired,
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(new_head) => head = new_head,
+            }
+        }
+    }
+}
+
+impl Default for Domain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Domain {
+    fn drop(&mut self) {
+        // Nobody can hold a hazard pointer anymore, so free everything.
+        let mut cur = *self.retired.get_mut();
+        while !cur.is_null() {
+            unsafe {
+                let retired = Box::from_raw(cur);
+                (retired.free)(retired.ptr);
+                cur = retired.next;
+            }
+        }
+
+        let mut cur = *self.records.get_mut();
+        while !cur.is_null() {
+            let record = unsafe { Box::from_raw(cur) };
+            cur = record.next;
+        }
+    }
+}
+
+// The domain only hands out records and frees retired nodes, which are only
+// ever read through atomics or after being unlinked.
+unsafe impl Send for Domain {}
+unsafe impl Sync for Domain {}
+
+impl<'a> Hazard<'a> {
+    /// Loads the pointer in `src` and protects it, so that it stays valid
+    /// while this hazard pointer is not reset.
+    pub(crate) fn protect<T>(&self, src: &AtomicPtr<T>) -> *mut T {
+        let mut ptr = src.load(Ordering::Relaxed);
+        loop {
+            self.record.hazard.store(ptr as *mut u8, Ordering::Relaxed);
+            fence(Ordering::SeqCst);
+
+            // The node could have been retired before we published the hazard
+            // pointer, so check that it is still reachable.
+            let now = src.load(Ordering::Acquire);
+            if now == ptr {
+                return ptr;
+            }
+            ptr = now;
+        }
+    }
+
+    /// Stops protecting the current pointer.
+    pub(crate) fn reset(&self) {
+        self.record.hazard.store(ptr::null_mut(), Ordering::Release);
+    }
+}
+
+impl<'a> Drop for Hazard<'a> {
+    fn drop(&mut self) {
+        self.reset();
+        self.record.active.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        ptr,
+        sync::{
+            atomic::{AtomicPtr, AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    use super::{Domain, RECLAIM_THRESHOLD};
+
+    struct D(Arc<AtomicUsize>);
+
+    impl Drop for D {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn hazards_are_reused() {
+        let domain = Domain::new();
+        let a = domain.hazard();
+        let b = domain.hazard();
+        assert!(!ptr::eq(a.record, b.record));
+
+        let a_record = a.record as *const _;
+        drop(a);
+        let c = domain.hazard();
+        assert!(ptr::eq(c.record, a_record));
+    }
+
+    #[test]
+    fn protected_nodes_are_not_freed() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let domain = Domain::new();
+
+        let protected = Box::into_raw(Box::new(D(drops.clone())));
+        let src = AtomicPtr::new(protected);
+        let hazard = domain.hazard();
+        assert_eq!(hazard.protect(&src), protected);
+        src.store(ptr::null_mut(), Ordering::Relaxed);
+
+        unsafe {
+            domain.retire(protected);
+            for _ in 1..RECLAIM_THRESHOLD {
+                domain.retire(Box::into_raw(Box::new(D(drops.clone()))));
+            }
+        }
+        assert_eq!(drops.load(Ordering::Relaxed), RECLAIM_THRESHOLD - 1);
+
+        drop(hazard);
+        drop(domain);
+        assert_eq!(drops.load(Ordering::Relaxed), RECLAIM_THRESHOLD);
+    }
+}
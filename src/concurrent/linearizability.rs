@@ -0,0 +1,108 @@
+//! A brute-force linearizability checker for small concurrent histories.
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+/// An operation on a collection, along with its result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Op {
+    Push(u32),
+    Pop(Option<u32>),
+}
+
+/// An operation, and the logical times at which it was invoked and returned.
+#[derive(Debug)]
+pub(crate) struct Event {
+    op: Op,
+    start: u64,
+    end: u64,
+}
+
+/// A logical clock shared by the threads of a test.
+#[derive(Clone)]
+pub(crate) struct Clock(Arc<AtomicU64>);
+
+impl Clock {
+    pub(crate) fn new() -> Self {
+        Self(Arc::new(AtomicU64::new(0)))
+    }
+
+    /// Runs `f`, recording the operation it performs.
+    pub(crate) fn record(&self, f: impl FnOnce() -> Op) -> Event {
+        let start = self.0.fetch_add(1, Ordering::SeqCst);
+        let op = f();
+        let end = self.0.fetch_add(1, Ordering::SeqCst);
+        Event { op, start, end }
+    }
+}
+
+/// Returns `true` if `history` can be ordered into a sequence that respects
+/// real-time order, and that `apply` accepts when run against `model`.
+pub(crate) fn check<M: Clone>(history: &[Event], model: M, apply: fn(&mut M, &Op) -> bool) -> bool {
+    fn search<M: Clone>(
+        history: &[Event],
+        done: &mut [bool],
+        model: &M,
+        apply: fn(&mut M, &Op) -> bool,
+    ) -> bool {
+        // An operation can go next only if no pending operation returned
+        // before it was invoked.
+        let Some(first_end) = history
+            .iter()
+            .zip(done.iter())
+            .filter(|(_, done)| !**done)
+            .map(|(event, _)| event.end)
+            .min()
+        else {
+            return true;
+        };
+
+        for i in 0..history.len() {
+            if done[i] || history[i].start > first_end {
+                continue;
+            }
+
+            let mut next = model.clone();
+            if apply(&mut next, &history[i].op) {
+                done[i] = true;
+                if search(history, done, &next, apply) {
+                    return true;
+                }
+                done[i] = false;
+            }
+        }
+        false
+    }
+
+    search(history, &mut vec![false; history.len()], &model, apply)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check, Event, Op};
+
+    fn apply(stack: &mut Vec<u32>, op: &Op) -> bool {
+        match *op {
+            Op::Push(elem) => {
+                stack.push(elem);
+                true
+            }
+            Op::Pop(elem) => stack.pop() == elem,
+        }
+    }
+
+    #[test]
+    fn real_time_order() {
+        let event = |op, start, end| Event { op, start, end };
+
+        // The pop overlaps the push, so it may be ordered before it.
+        let history = [event(Op::Push(1), 0, 3), event(Op::Pop(None), 1, 2)];
+        assert!(check(&history, Vec::new(), apply));
+
+        // The pop starts after the push returned, so it must see it.
+        let history = [event(Op::Push(1), 0, 1), event(Op::Pop(None), 2, 3)];
+        assert!(!check(&history, Vec::new(), apply));
+    }
+}
@@ -0,0 +1,338 @@
+use std::{
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+use super::hazard::Domain;
+
+/// A lock-free multi-producer multi-consumer queue (Michael–Scott queue),
+/// shaped like [`fifth::List`](crate::fifth::List) but with atomic head and
+/// tail pointers.
+///
+/// The head always points to a dummy node, whose element was already popped
+/// (or never existed), so that producers and consumers never contend on the
+/// same pointer unless the queue is empty.
+pub struct Queue<T> {
+    head: AtomicPtr<Node<T>>,
+    tail: AtomicPtr<Node<T>>,
+    domain: Domain,
+    _marker: PhantomData<T>,
+}
+
+struct Node<T> {
+    // Uninitialized in the dummy node.
+    elem: MaybeUninit<T>,
+    next: AtomicPtr<Node<T>>,
+}
+
+impl<T> Queue<T> {
+    /// Creates an empty [`Queue`].
+    pub fn new() -> Self {
+        let dummy = Box::into_raw(Box::new(Node {
+            elem: MaybeUninit::uninit(),
+            next: AtomicPtr::new(ptr::null_mut()),
+        }));
+
+        Queue {
+            head: AtomicPtr::new(dummy),
+            tail: AtomicPtr::new(dummy),
+            domain: Domain::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Inserts a new element at the back of the queue.
+    pub fn push_back(&self, elem: T) {
+        let new_tail = Box::into_raw(Box::new(Node {
+            elem: MaybeUninit::new(elem),
+            next: AtomicPtr::new(ptr::null_mut()),
+        }));
+
+        let hazard = self.domain.hazard();
+        loop {
+            let tail = hazard.protect(&self.tail);
+            let next = unsafe { (*tail).next.load(Ordering::Acquire) };
+
+            if next.is_null() {
+                if unsafe { &(*tail).next }
+                    .compare_exchange(next, new_tail, Ordering::Release, Ordering::Relaxed)
+                    .is_ok()
+                {
+                    // Failing is fine, someone else already swung the tail.
+                    let _ = self.tail.compare_exchange(
+                        tail,
+                        new_tail,
+                        Ordering::Release,
+                        Ordering::Relaxed,
+                    );
+                    return;
+                }
+            } else {
+                // The tail is lagging behind, help the other producer.
+                let _ =
+                    self.tail
+                        .compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Removes an element from the front of the queue and returns it, or
+    /// [`None`] if it is empty.
+    pub fn pop_front(&self) -> Option<T> {
+        let head_hazard = self.domain.hazard();
+        let next_hazard = self.domain.hazard();
+        loop {
+            let head = head_hazard.protect(&self.head);
+            let next = next_hazard.protect(unsafe { &(*head).next });
+
+            // `next` can only be retired after `head` is, so it is safe to use
+            // as long as `head` is still the head.
+            if self.head.load(Ordering::Acquire) != head {
+                continue;
+            }
+            if next.is_null() {
+                return None;
+            }
+
+            let tail = self.tail.load(Ordering::Acquire);
+            if head == tail {
+                // Never let the tail fall behind the head, or it could point to
+                // a retired node.
+                let _ =
+                    self.tail
+                        .compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+                continue;
+            }
+
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+            {
+                unsafe {
+                    // `next` is the new dummy, and only we may move its element out.
+                    let elem = (*next).elem.as_ptr().read();
+                    head_hazard.reset();
+                    next_hazard.reset();
+                    self.domain.retire(head);
+                    return Some(elem);
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if the queue was empty when checked.
+    pub fn is_empty(&self) -> bool {
+        let hazard = self.domain.hazard();
+        let head = hazard.protect(&self.head);
+        unsafe { (*head).next.load(Ordering::Acquire).is_null() }
+    }
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Queue<T> {
+    fn drop(&mut self) {
+        unsafe {
+            // The dummy's element was already moved out, so only free it.
+            let mut dummy = Box::from_raw(*self.head.get_mut());
+            let mut cur = *dummy.next.get_mut();
+
+            while !cur.is_null() {
+                let mut boxed_node = Box::from_raw(cur);
+                cur = *boxed_node.next.get_mut();
+                boxed_node.elem.assume_init_drop();
+            }
+        }
+    }
+}
+
+// Elements are moved between threads, but never shared between them.
+unsafe impl<T: Send> Send for Queue<T> {}
+unsafe impl<T: Send> Sync for Queue<T> {}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        collections::VecDeque,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Barrier,
+        },
+        thread,
+    };
+
+    use super::Queue;
+    use crate::concurrent::linearizability::{self, Op};
+    use crate::util::test::assert_send_sync;
+
+    #[test]
+    fn basics() {
+        let queue = Queue::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.pop_front(), None);
+
+        queue.push_back(1);
+        queue.push_back(2);
+        queue.push_back(3);
+        assert!(!queue.is_empty());
+
+        assert_eq!(queue.pop_front(), Some(1));
+        assert_eq!(queue.pop_front(), Some(2));
+
+        queue.push_back(4);
+        queue.push_back(5);
+
+        assert_eq!(queue.pop_front(), Some(3));
+        assert_eq!(queue.pop_front(), Some(4));
+        assert_eq!(queue.pop_front(), Some(5));
+        assert_eq!(queue.pop_front(), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn send_sync() {
+        assert_send_sync::<Queue<String>>();
+    }
+
+    #[test]
+    fn drop_remaining() {
+        let drops = Arc::new(AtomicUsize::new(0));
+
+        struct D(Arc<AtomicUsize>);
+
+        impl Drop for D {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let queue = Queue::new();
+        for _ in 0..10 {
+            queue.push_back(D(drops.clone()));
+        }
+        drop(queue.pop_front());
+        drop(queue.pop_front());
+        assert_eq!(drops.load(Ordering::Relaxed), 2);
+
+        drop(queue);
+        assert_eq!(drops.load(Ordering::Relaxed), 10);
+    }
+
+    #[test]
+    fn stress() {
+        const PRODUCERS: usize = 4;
+        const CONSUMERS: usize = 4;
+        const PER_PRODUCER: usize = 10_000;
+
+        let queue = Arc::new(Queue::new());
+        let producers: Vec<_> = (0..PRODUCERS)
+            .map(|p| {
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        queue.push_back((p, i));
+                    }
+                })
+            })
+            .collect();
+
+        let popped = Arc::new(AtomicUsize::new(0));
+        let consumers: Vec<_> = (0..CONSUMERS)
+            .map(|_| {
+                let (queue, popped) = (queue.clone(), popped.clone());
+                thread::spawn(move || {
+                    let mut received = Vec::new();
+                    while popped.load(Ordering::Relaxed) < PRODUCERS * PER_PRODUCER {
+                        if let Some(elem) = queue.pop_front() {
+                            popped.fetch_add(1, Ordering::Relaxed);
+                            received.push(elem);
+                        }
+                    }
+                    received
+                })
+            })
+            .collect();
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        let mut seen = vec![vec![false; PER_PRODUCER]; PRODUCERS];
+        for consumer in consumers {
+            let received = consumer.join().unwrap();
+
+            // Each consumer sees the elements of a producer in FIFO order.
+            let mut last = [None; PRODUCERS];
+            for (p, i) in received {
+                assert!(last[p] < Some(i), "({}, {}) was popped out of order", p, i);
+                last[p] = Some(i);
+
+                assert!(!seen[p][i], "({}, {}) was popped twice", p, i);
+                seen[p][i] = true;
+            }
+        }
+
+        assert!(
+            seen.iter().flatten().all(|seen| *seen),
+            "an element was lost"
+        );
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn linearizable() {
+        const THREADS: usize = 3;
+        const OPS: usize = 4;
+
+        for _ in 0..200 {
+            let queue = Arc::new(Queue::new());
+            let clock = linearizability::Clock::new();
+            let barrier = Arc::new(Barrier::new(THREADS));
+
+            let handles: Vec<_> = (0..THREADS)
+                .map(|t| {
+                    let (queue, clock, barrier) = (queue.clone(), clock.clone(), barrier.clone());
+                    thread::spawn(move || {
+                        barrier.wait();
+                        (0..OPS)
+                            .map(|i| {
+                                if (t + i) % 2 == 0 {
+                                    let elem = (t * OPS + i) as u32;
+                                    clock.record(|| {
+                                        queue.push_back(elem);
+                                        Op::Push(elem)
+                                    })
+                                } else {
+                                    clock.record(|| Op::Pop(queue.pop_front()))
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            let history: Vec<_> = handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect();
+            assert!(
+                linearizability::check(&history, VecDeque::new(), |queue, op| match *op {
+                    Op::Push(elem) => {
+                        queue.push_back(elem);
+                        true
+                    }
+                    Op::Pop(elem) => queue.pop_front() == elem,
+                }),
+                "not linearizable: {:?}",
+                history
+            );
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use std::{
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+use super::hazard::Domain;
+
+/// A lock-free stack (Treiber stack), shaped like [`second::List`](crate::second::List)
+/// but with an atomic head that is updated with compare-and-swap.
+pub struct Stack<T> {
+    head: AtomicPtr<Node<T>>,
+    domain: Domain,
+    _marker: PhantomData<T>,
+}
+
+struct Node<T> {
+    // Moved out by `pop` before the node is retired.
+    elem: ManuallyDrop<T>,
+    // Never changes once the node is published.
+    next: *mut Node<T>,
+}
+
+impl<T> Stack<T> {
+    /// Creates an empty [`Stack`].
+    pub fn new() -> Self {
+        Stack {
+            head: AtomicPtr::new(ptr::null_mut()),
+            domain: Domain::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Adds an element to the top of the stack.
+    pub fn push(&self, elem: T) {
+        let new_node = Box::into_raw(Box::new(Node {
+            elem: ManuallyDrop::new(elem),
+            next: ptr::null_mut(),
+        }));
+
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            unsafe { (*new_node).next = head };
+            match self.head.compare_exchange_weak(
+                head,
+                new_node,
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(new_head) => head = new_head,
+            }
+        }
+    }
+
+    /// Removes the top element from the stack and returns it, or [`None`] if it is empty.
+    pub fn pop(&self) -> Option<T> {
+        let hazard = self.domain.hazard();
+        loop {
+            // Protecting the head keeps it from being freed, and from coming
+            // back at the same address (ABA) while we read its `next`.
+            let head = hazard.protect(&self.head);
+            if head.is_null() {
+                return None;
+            }
+
+            let next = unsafe { (*head).next };
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+            {
+                hazard.reset();
+                unsafe {
+                    // Only the thread that unlinked the node reads its element.
+                    let elem = ptr::read(&(*head).elem);
+                    self.domain.retire(head);
+                    return Some(ManuallyDrop::into_inner(elem));
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if the stack was empty when checked.
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire).is_null()
+    }
+}
+
+impl<T> Default for Stack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Stack<T> {
+    fn drop(&mut self) {
+        let mut cur = *self.head.get_mut();
+        while !cur.is_null() {
+            let mut boxed_node = unsafe { Box::from_raw(cur) };
+            cur = boxed_node.next;
+            unsafe { ManuallyDrop::drop(&mut boxed_node.elem) };
+        }
+    }
+}
+
+// Elements are moved between threads, but never shared between them.
+unsafe impl<T: Send> Send for Stack<T> {}
+unsafe impl<T: Send> Sync for Stack<T> {}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        collections::HashSet,
+        sync::{Arc, Barrier},
+        thread,
+    };
+
+    use super::Stack;
+    use crate::concurrent::linearizability::{self, Op};
+    use crate::util::test::assert_send_sync;
+
+    #[test]
+    fn basics() {
+        let stack = Stack::new();
+        assert!(stack.is_empty());
+        assert_eq!(stack.pop(), None);
+
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+        assert!(!stack.is_empty());
+
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+
+        stack.push(4);
+        stack.push(5);
+
+        assert_eq!(stack.pop(), Some(5));
+        assert_eq!(stack.pop(), Some(4));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+    }
+
+    #[test]
+    fn send_sync() {
+        assert_send_sync::<Stack<String>>();
+    }
+
+    #[test]
+    fn drop_long() {
+        let stack = Stack::new();
+        for i in 0..100_000 {
+            stack.push(Box::new(i));
+        }
+    }
+
+    #[test]
+    fn stress() {
+        const THREADS: usize = 8;
+        const PER_THREAD: usize = 10_000;
+
+        let stack = Arc::new(Stack::new());
+        let handles: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let stack = stack.clone();
+                thread::spawn(move || {
+                    let mut popped = Vec::new();
+                    for i in 0..PER_THREAD {
+                        stack.push(t * PER_THREAD + i);
+                        if i % 2 == 1 {
+                            popped.extend(stack.pop());
+                        }
+                    }
+                    popped
+                })
+            })
+            .collect();
+
+        let mut seen = HashSet::new();
+        for handle in handles {
+            for elem in handle.join().unwrap() {
+                assert!(seen.insert(elem), "{} was popped twice", elem);
+            }
+        }
+        while let Some(elem) = stack.pop() {
+            assert!(seen.insert(elem), "{} was popped twice", elem);
+        }
+        assert_eq!(seen.len(), THREADS * PER_THREAD);
+    }
+
+    #[test]
+    fn linearizable() {
+        const THREADS: usize = 3;
+        const OPS: usize = 4;
+
+        for _ in 0..200 {
+            let stack = Arc::new(Stack::new());
+            let clock = linearizability::Clock::new();
+            let barrier = Arc::new(Barrier::new(THREADS));
+
+            let handles: Vec<_> = (0..THREADS)
+                .map(|t| {
+                    let (stack, clock, barrier) = (stack.clone(), clock.clone(), barrier.clone());
+                    thread::spawn(move || {
+                        barrier.wait();
+                        (0..OPS)
+                            .map(|i| {
+                                if (t + i) % 2 == 0 {
+                                    let elem = (t * OPS + i) as u32;
+                                    clock.record(|| {
+                                        stack.push(elem);
+                                        Op::Push(elem)
+                                    })
+                                } else {
+                                    clock.record(|| Op::Pop(stack.pop()))
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            let history: Vec<_> = handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect();
+            assert!(
+                linearizability::check(&history, Vec::new(), |stack, op| match *op {
+                    Op::Push(elem) => {
+                        stack.push(elem);
+                        true
+                    }
+                    Op::Pop(elem) => stack.pop() == elem,
+                }),
+                "not linearizable: {:?}",
+                history
+            );
+        }
+    }
+}
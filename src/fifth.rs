@@ -1,3 +1,6 @@
+use crate::util::drop_all;
+use crate::viz::{Dot, Viz};
+
 pub struct List<T> {
     head: *mut Node<T>,
     tail: *mut Node<T>,
@@ -55,6 +58,22 @@ impl<T> List<T> {
         }
     }
 
+    /// Reverses the list in place, by relinking its nodes.
+    pub fn reverse(&mut self) {
+        let mut reversed = core::ptr::null_mut();
+        let mut cur = self.head;
+        unsafe {
+            while !cur.is_null() {
+                let next = (*cur).next;
+                (*cur).next = reversed;
+                reversed = cur;
+                cur = next;
+            }
+        }
+        self.tail = self.head;
+        self.head = reversed;
+    }
+
     /// Returns an iterator over the elements in the list.
     pub fn into_iter(self) -> IntoIter<T> {
         IntoIter(self)
@@ -91,7 +110,24 @@ impl<T> List<T> {
 
 impl<T> Drop for List<T> {
     fn drop(&mut self) {
-        while let Some(_) = self.pop_front() {}
+        drop_all(self, Self::pop_front);
+    }
+}
+
+impl<T: core::fmt::Debug> Viz for List<T> {
+    fn viz(&self, name: &str, dot: &mut Dot) {
+        dot.entry(name, name);
+        dot.pointer(name, "head", (!self.head.is_null()).then_some(self.head));
+        dot.pointer(name, "tail", (!self.tail.is_null()).then_some(self.tail));
+
+        let mut cur = self.head;
+        unsafe {
+            while let Some(node) = cur.as_ref() {
+                dot.node(node, &node.elem, None);
+                dot.edge(node, (!node.next.is_null()).then_some(node.next), "next");
+                cur = node.next;
+            }
+        }
     }
 }
 
@@ -142,6 +178,7 @@ impl<'a, T> Iterator for IterMut<'a, T> {
 #[cfg(test)]
 mod test {
     use super::List;
+    use crate::util::test::{panics, Drops};
 
     #[test]
     fn basics() {
@@ -235,4 +272,36 @@ mod test {
         assert_eq!(&*list.peek().unwrap(), &1);
         assert_eq!(&mut *list.peek_mut().unwrap(), &mut 1);
     }
+
+    #[test]
+    fn drop_panic() {
+        let drops = Drops::default();
+        let mut list = List::new();
+        list.push_back(drops.elem(false));
+        list.push_back(drops.elem(true));
+        list.push_back(drops.elem(false));
+        list.push_back(drops.elem(false));
+
+        assert!(panics(move || drop(list)));
+        assert_eq!(drops.count(), 4);
+    }
+
+    #[test]
+    fn reverse() {
+        let mut list = List::new();
+        list.reverse();
+        assert_eq!(list.pop_front(), None);
+
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+        list.reverse();
+        assert_eq!(list.iter().collect::<Vec<_>>(), [&3, &2, &1]);
+
+        // The tail moved to the old head.
+        list.push_back(4);
+        assert_eq!(list.pop_front(), Some(3));
+        list.reverse();
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), [4, 1, 2]);
+    }
 }
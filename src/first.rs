@@ -1,3 +1,5 @@
+use crate::viz::{Dot, Viz};
+
 #[derive(Debug)]
 struct Node<T> {
     elem: T,
@@ -52,6 +54,27 @@ impl<T> Drop for List<T> {
     }
 }
 
+impl<T: core::fmt::Debug> Viz for List<T> {
+    fn viz(&self, name: &str, dot: &mut Dot) {
+        dot.entry(name, name);
+
+        let mut cur_link = &self.head;
+        dot.pointer(name, "head", link_ptr(cur_link));
+        while let Link::More(node) = cur_link {
+            dot.node(&**node, &node.elem, None);
+            dot.edge(&**node, link_ptr(&node.next), "next");
+            cur_link = &node.next;
+        }
+    }
+}
+
+fn link_ptr<T>(link: &Link<T>) -> Option<*const Node<T>> {
+    match link {
+        Link::Empty => None,
+        Link::More(node) => Some(&**node),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::List;
@@ -3,6 +3,8 @@ use std::{
     rc::Rc,
 };
 
+use crate::viz::{Dot, Viz};
+
 pub struct List<T> {
     head: Link<T>,
     tail: Link<T>,
@@ -123,6 +125,31 @@ impl<T> Drop for List<T> {
     }
 }
 
+impl<T: std::fmt::Debug> Viz for List<T> {
+    fn viz(&self, name: &str, dot: &mut Dot) {
+        dot.entry(name, name);
+        dot.pointer(name, "head", self.head.as_ref().map(Rc::as_ptr));
+        dot.pointer(name, "tail", self.tail.as_ref().map(Rc::as_ptr));
+
+        let mut link = self.head.clone();
+        while let Some(node) = link {
+            let borrowed = node.borrow();
+            dot.node(Rc::as_ptr(&node), &borrowed.elem, None);
+            dot.edge(
+                Rc::as_ptr(&node),
+                borrowed.next.as_ref().map(Rc::as_ptr),
+                "next",
+            );
+            dot.edge(
+                Rc::as_ptr(&node),
+                borrowed.prev.as_ref().map(Rc::as_ptr),
+                "prev",
+            );
+            link = borrowed.next.clone();
+        }
+    }
+}
+
 impl<T> Node<T> {
     /// Creates a new node.
     fn new(elem: T) -> Rc<RefCell<Self>> {
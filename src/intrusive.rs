@@ -0,0 +1,890 @@
+//! An intrusive doubly linked list, whose nodes are embedded in caller-owned
+//! objects instead of being allocated by the list.
+//!
+//! An object embeds one [`Links`] field per list it can be part of, and an
+//! [`Adapter`] tells a list which field to use, so the same object can sit in
+//! several lists at once without any extra allocation.
+//!
+//! The list is circular around a sentinel [`Links`] stored in the list itself,
+//! which plays the role of the ghost element of
+//! [`linked_list::CursorMut`](crate::linked_list::CursorMut). Linked objects
+//! point into each other and into the list, so both have to be pinned.
+
+use core::{
+    cell::Cell,
+    fmt::{self, Debug},
+    marker::{PhantomData, PhantomPinned},
+    pin::Pin,
+    ptr,
+};
+
+/// The links embedded in an object, for one list.
+pub struct Links {
+    prev: Cell<*const Links>,
+    next: Cell<*const Links>,
+    _pin: PhantomPinned,
+}
+
+/// Maps an object to the [`Links`] a list uses.
+///
+/// # Safety
+///
+/// `links` must return a pointer to a field of the object, and `object` must
+/// be its exact inverse.
+pub unsafe trait Adapter {
+    /// The type of the linked objects.
+    type Object;
+
+    /// The pointer the list holds the objects with.
+    type Pointer: Pointer<Target = Self::Object>;
+
+    /// Returns the links of `obj` used by the list.
+    ///
+    /// This must be implemented with [`ptr::addr_of!`]`((*obj).field)`
+    /// rather than through a reference, so that the links keep the
+    /// provenance of the whole object. The list turns them back into the
+    /// object with [`Adapter::object`].
+    ///
+    /// # Safety
+    ///
+    /// `obj` must point to a live object.
+    unsafe fn links(obj: *const Self::Object) -> *const Links;
+
+    /// Returns the object containing `links`.
+    ///
+    /// # Safety
+    ///
+    /// `links` must be the links of an object of this adapter.
+    unsafe fn object(links: *const Links) -> *const Self::Object;
+}
+
+/// A pointer to a pinned object, that can be stored in an [`IntrusiveList`].
+///
+/// # Safety
+///
+/// `from_raw` must be the inverse of `into_raw`, and the object must stay
+/// pinned in between.
+pub unsafe trait Pointer {
+    /// The type of the pointed object.
+    type Target;
+
+    /// Turns the pointer into a raw pointer.
+    fn into_raw(this: Self) -> *const Self::Target;
+
+    /// Recovers the pointer from a raw pointer returned by `into_raw`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must come from `into_raw`, and must not be recovered twice.
+    unsafe fn from_raw(ptr: *const Self::Target) -> Self;
+}
+
+/// An intrusive doubly linked list.
+///
+/// Mutating the list requires it to be pinned, e.g. with [`core::pin::pin!`] or
+/// [`Box::pin`].
+pub struct IntrusiveList<A: Adapter> {
+    // Null until the first insertion, then points to itself when empty.
+    sentinel: Links,
+    _marker: PhantomData<A::Pointer>,
+}
+
+impl Links {
+    /// Creates unlinked [`Links`].
+    pub const fn new() -> Self {
+        Self {
+            prev: Cell::new(ptr::null()),
+            next: Cell::new(ptr::null()),
+            _pin: PhantomPinned,
+        }
+    }
+
+    /// Returns `true` if the object is part of a list.
+    pub fn is_linked(&self) -> bool {
+        !self.next.get().is_null()
+    }
+
+    /// Removes the object from the list it is part of, without knowing the
+    /// list, and returns `true` if it was part of one.
+    ///
+    /// If the list owned the object (e.g. with a [`Pin<Box<T>>`]), ownership
+    /// is not given back, and the object is leaked.
+    pub fn unlink(&self) -> bool {
+        let prev = self.prev.get();
+        let next = self.next.get();
+        if next.is_null() {
+            return false;
+        }
+
+        unsafe {
+            (*prev).next.set(next);
+            (*next).prev.set(prev);
+        }
+        self.prev.set(ptr::null());
+        self.next.set(ptr::null());
+        true
+    }
+
+    /// Moves the objects from `first` to `last` out of their list, and links
+    /// them between `prev` and `next`, which must belong to another list.
+    unsafe fn move_between(
+        first: *const Links,
+        last: *const Links,
+        prev: *const Links,
+        next: *const Links,
+    ) {
+        let before = (*first).prev.get();
+        let after = (*last).next.get();
+        (*before).next.set(after);
+        (*after).prev.set(before);
+
+        (*first).prev.set(prev);
+        (*last).next.set(next);
+        (*prev).next.set(first);
+        (*next).prev.set(last);
+    }
+
+    /// Links `this` between `prev` and `next`.
+    ///
+    /// Takes a raw pointer, since the neighbours keep it to get back to the
+    /// whole object later.
+    unsafe fn link_between(this: *const Links, prev: *const Links, next: *const Links) {
+        (*this).prev.set(prev);
+        (*this).next.set(next);
+        (*prev).next.set(this);
+        (*next).prev.set(this);
+    }
+}
+
+impl Default for Links {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Links {
+    fn drop(&mut self) {
+        // Never leave dangling pointers in a list.
+        self.unlink();
+    }
+}
+
+impl Debug for Links {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Links")
+            .field("linked", &self.is_linked())
+            .finish()
+    }
+}
+
+unsafe impl<T> Pointer for Pin<Box<T>> {
+    type Target = T;
+
+    fn into_raw(this: Self) -> *const T {
+        Box::into_raw(unsafe { Pin::into_inner_unchecked(this) })
+    }
+
+    unsafe fn from_raw(ptr: *const T) -> Self {
+        Box::into_pin(Box::from_raw(ptr as *mut T))
+    }
+}
+
+unsafe impl<T> Pointer for Pin<&T> {
+    type Target = T;
+
+    fn into_raw(this: Self) -> *const T {
+        Pin::get_ref(this)
+    }
+
+    unsafe fn from_raw(ptr: *const T) -> Self {
+        Pin::new_unchecked(&*ptr)
+    }
+}
+
+unsafe impl<T> Pointer for Pin<&mut T> {
+    type Target = T;
+
+    fn into_raw(this: Self) -> *const T {
+        // Go through `*mut T`, since coercing to `*const T` directly would
+        // only keep read access to the object.
+        let ptr: *mut T = unsafe { Pin::get_unchecked_mut(this) };
+        ptr
+    }
+
+    unsafe fn from_raw(ptr: *const T) -> Self {
+        Pin::new_unchecked(&mut *(ptr as *mut T))
+    }
+}
+
+impl<A: Adapter> IntrusiveList<A> {
+    /// Creates a new [`IntrusiveList`].
+    pub const fn new() -> Self {
+        Self {
+            sentinel: Links::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the sentinel, making it point to itself if the list was never used.
+    fn sentinel(self: Pin<&mut Self>) -> *const Links {
+        let sentinel = &self.sentinel as *const Links;
+        if !self.sentinel.is_linked() {
+            self.sentinel.prev.set(sentinel);
+            self.sentinel.next.set(sentinel);
+        }
+        sentinel
+    }
+
+    /// Returns the links of the first object, or `None` if the list is empty.
+    fn first(&self) -> Option<*const Links> {
+        let next = self.sentinel.next.get();
+        (!next.is_null() && !ptr::eq(next, &self.sentinel)).then_some(next)
+    }
+
+    /// Returns the links of the last object, or `None` if the list is empty.
+    fn last(&self) -> Option<*const Links> {
+        let prev = self.sentinel.prev.get();
+        (!prev.is_null() && !ptr::eq(prev, &self.sentinel)).then_some(prev)
+    }
+
+    /// Inserts an object at the beginning of the list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the object is already part of a list using the same links.
+    pub fn push_front(self: Pin<&mut Self>, ptr: A::Pointer) {
+        let sentinel = self.sentinel();
+        unsafe {
+            let links = link::<A>(ptr);
+            Links::link_between(links, sentinel, (*sentinel).next.get());
+        }
+    }
+
+    /// Inserts an object at the end of the list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the object is already part of a list using the same links.
+    pub fn push_back(self: Pin<&mut Self>, ptr: A::Pointer) {
+        let sentinel = self.sentinel();
+        unsafe {
+            let links = link::<A>(ptr);
+            Links::link_between(links, (*sentinel).prev.get(), sentinel);
+        }
+    }
+
+    /// Removes the first object of the list, and returns it.
+    pub fn pop_front(self: Pin<&mut Self>) -> Option<A::Pointer> {
+        self.first().map(|links| unsafe { unlink::<A>(links) })
+    }
+
+    /// Removes the last object of the list, and returns it.
+    pub fn pop_back(self: Pin<&mut Self>) -> Option<A::Pointer> {
+        self.last().map(|links| unsafe { unlink::<A>(links) })
+    }
+
+    /// Removes an object from the list in O(1), and returns it.
+    ///
+    /// # Safety
+    ///
+    /// `obj` must be part of this list, and must not be used after this call,
+    /// since the returned pointer may own the object or borrow it mutably
+    /// (e.g. with a [`Pin<&mut T>`]).
+    pub unsafe fn remove(self: Pin<&mut Self>, obj: &A::Object) -> A::Pointer {
+        // `obj` only grants shared access, so take the pointer the previous
+        // node holds instead, which has the provenance the object was linked
+        // with.
+        let links = A::links(obj);
+        unlink::<A>((*(*links).prev.get()).next.get())
+    }
+
+    /// Returns a reference to the first object of the list.
+    pub fn front(&self) -> Option<&A::Object> {
+        self.first().map(|links| unsafe { &*A::object(links) })
+    }
+
+    /// Returns a reference to the last object of the list.
+    pub fn back(&self) -> Option<&A::Object> {
+        self.last().map(|links| unsafe { &*A::object(links) })
+    }
+
+    /// Returns `true` if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.first().is_none()
+    }
+
+    /// Returns an iterator over the objects of the list.
+    pub fn iter(&self) -> Iter<'_, A> {
+        Iter {
+            next: self.first(),
+            sentinel: &self.sentinel,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns a cursor at the ghost element of the list.
+    pub fn cursor_mut(mut self: Pin<&mut Self>) -> CursorMut<'_, A> {
+        let sentinel = self.as_mut().sentinel();
+        CursorMut {
+            cur: sentinel,
+            list: self,
+        }
+    }
+}
+
+/// Takes the object behind `ptr` over, and returns its (still unlinked) links.
+unsafe fn link<A: Adapter>(ptr: A::Pointer) -> *const Links {
+    let obj = A::Pointer::into_raw(ptr);
+    let links = A::links(obj);
+    if (*links).is_linked() {
+        // Give the pointer back, so that it is dropped as usual.
+        drop(A::Pointer::from_raw(obj));
+        panic!("object is already linked");
+    }
+    links
+}
+
+/// Unlinks `links`, and returns the pointer to its object.
+unsafe fn unlink<A: Adapter>(links: *const Links) -> A::Pointer {
+    (*links).unlink();
+    A::Pointer::from_raw(A::object(links))
+}
+
+impl<A: Adapter> Default for IntrusiveList<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Adapter> Drop for IntrusiveList<A> {
+    fn drop(&mut self) {
+        // The list was pinned if it has any objects, and stays so until now.
+        let mut this = unsafe { Pin::new_unchecked(self) };
+        while this.as_mut().pop_front().is_some() {}
+    }
+}
+
+impl<A: Adapter> Debug for IntrusiveList<A>
+where
+    A::Object: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// An iterator over the objects of an [`IntrusiveList`].
+pub struct Iter<'a, A: Adapter> {
+    next: Option<*const Links>,
+    sentinel: *const Links,
+    _marker: PhantomData<&'a A::Object>,
+}
+
+impl<'a, A: Adapter> Iterator for Iter<'a, A> {
+    type Item = &'a A::Object;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|links| unsafe {
+            let next = (*links).next.get();
+            // Stop early if the object unlinked itself meanwhile.
+            self.next = (!next.is_null() && next != self.sentinel).then_some(next);
+            &*A::object(links)
+        })
+    }
+}
+
+/// A cursor over an [`IntrusiveList`], with the same shape as
+/// [`linked_list::CursorMut`](crate::linked_list::CursorMut).
+///
+/// A list cannot be moved once objects point to its sentinel, so splitting
+/// moves objects into another pinned list instead of returning a new one, and
+/// splicing empties the given list. There is no `index`, since objects can
+/// unlink themselves with [`Links::unlink`] without the list knowing, so
+/// positions are not tracked.
+pub struct CursorMut<'a, A: Adapter> {
+    // The sentinel when at the ghost element.
+    cur: *const Links,
+    list: Pin<&'a mut IntrusiveList<A>>,
+}
+
+impl<'a, A: Adapter> CursorMut<'a, A> {
+    fn is_ghost(&self) -> bool {
+        ptr::eq(self.cur, &self.list.sentinel)
+    }
+
+    fn sentinel(&self) -> *const Links {
+        &self.list.sentinel
+    }
+
+    /// Moves the cursor to `to`, or to the ghost element if the current object
+    /// unlinked itself.
+    fn move_to(&mut self, to: *const Links) {
+        self.cur = if to.is_null() { self.sentinel() } else { to };
+    }
+
+    /// Move the cursor to the next position.
+    pub fn move_next(&mut self) {
+        let next = unsafe { (*self.cur).next.get() };
+        self.move_to(next);
+    }
+
+    /// Move the cursor to the previous position.
+    pub fn move_prev(&mut self) {
+        let prev = unsafe { (*self.cur).prev.get() };
+        self.move_to(prev);
+    }
+
+    /// Retrieve the object at the cursor.
+    pub fn current(&mut self) -> Option<&A::Object> {
+        if self.is_ghost() || !unsafe { (*self.cur).is_linked() } {
+            None
+        } else {
+            unsafe { Some(&*A::object(self.cur)) }
+        }
+    }
+
+    /// Retrieve the object next to the cursor.
+    pub fn peek_next(&mut self) -> Option<&A::Object> {
+        let next = unsafe { (*self.cur).next.get() };
+        if next.is_null() || ptr::eq(next, self.sentinel()) {
+            None
+        } else {
+            unsafe { Some(&*A::object(next)) }
+        }
+    }
+
+    /// Retrieve the object before the cursor.
+    pub fn peek_prev(&mut self) -> Option<&A::Object> {
+        let prev = unsafe { (*self.cur).prev.get() };
+        if prev.is_null() || ptr::eq(prev, self.sentinel()) {
+            None
+        } else {
+            unsafe { Some(&*A::object(prev)) }
+        }
+    }
+
+    /// Inserts an object before the cursor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the object is already part of a list using the same links.
+    pub fn insert_before(&mut self, ptr: A::Pointer) {
+        self.reattach();
+        unsafe {
+            let links = link::<A>(ptr);
+            Links::link_between(links, (*self.cur).prev.get(), self.cur);
+        }
+    }
+
+    /// Inserts an object after the cursor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the object is already part of a list using the same links.
+    pub fn insert_after(&mut self, ptr: A::Pointer) {
+        self.reattach();
+        unsafe {
+            let links = link::<A>(ptr);
+            Links::link_between(links, self.cur, (*self.cur).next.get());
+        }
+    }
+
+    /// Removes the current object and returns it.
+    /// The cursor will be moved to the next object.
+    pub fn remove_current(&mut self) -> Option<A::Pointer> {
+        self.current()?;
+
+        unsafe {
+            let cur = self.cur;
+            self.move_next();
+            Some(unlink::<A>(cur))
+        }
+    }
+
+    /// Moves the objects before the cursor to the back of `into`.
+    /// At the ghost element, moves the whole list.
+    pub fn split_before(&mut self, into: Pin<&mut IntrusiveList<A>>) {
+        self.reattach();
+        unsafe {
+            let first = (*self.sentinel()).next.get();
+            if ptr::eq(first, self.cur) {
+                return;
+            }
+            let last = (*self.cur).prev.get();
+            let into = into.sentinel();
+            Links::move_between(first, last, (*into).prev.get(), into);
+        }
+    }
+
+    /// Moves the objects after the cursor to the front of `into`.
+    /// At the ghost element, moves the whole list.
+    pub fn split_after(&mut self, into: Pin<&mut IntrusiveList<A>>) {
+        self.reattach();
+        unsafe {
+            let last = (*self.sentinel()).prev.get();
+            if ptr::eq(last, self.cur) {
+                return;
+            }
+            let first = (*self.cur).next.get();
+            let into = into.sentinel();
+            Links::move_between(first, last, into, (*into).next.get());
+        }
+    }
+
+    /// Moves all the objects of `input` before the cursor, leaving it empty.
+    /// At the ghost element, they go to the back of the list.
+    pub fn splice_before(&mut self, input: Pin<&mut IntrusiveList<A>>) {
+        self.reattach();
+        let input = input.sentinel();
+        unsafe {
+            let first = (*input).next.get();
+            if !ptr::eq(first, input) {
+                let last = (*input).prev.get();
+                Links::move_between(first, last, (*self.cur).prev.get(), self.cur);
+            }
+        }
+    }
+
+    /// Moves all the objects of `input` after the cursor, leaving it empty.
+    /// At the ghost element, they go to the front of the list.
+    pub fn splice_after(&mut self, input: Pin<&mut IntrusiveList<A>>) {
+        self.reattach();
+        let input = input.sentinel();
+        unsafe {
+            let first = (*input).next.get();
+            if !ptr::eq(first, input) {
+                let last = (*input).prev.get();
+                Links::move_between(first, last, self.cur, (*self.cur).next.get());
+            }
+        }
+    }
+
+    /// Moves the cursor to the ghost element if the current object unlinked
+    /// itself, since there is nothing to insert next to anymore.
+    fn reattach(&mut self) {
+        if !unsafe { (*self.cur).is_linked() } {
+            self.cur = self.sentinel();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::{cell::Cell, marker::PhantomData, mem::offset_of, pin::pin, pin::Pin, ptr};
+
+    use super::{Adapter, IntrusiveList, Links};
+
+    #[derive(Default)]
+    struct Task {
+        id: u32,
+        run: Links,
+        all: Links,
+    }
+
+    impl core::fmt::Debug for Task {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            self.id.fmt(f)
+        }
+    }
+
+    fn task(id: u32) -> Task {
+        Task {
+            id,
+            ..Default::default()
+        }
+    }
+
+    /// Links tasks by their `run` field, borrowing them.
+    struct RunAdapter<'a>(PhantomData<&'a ()>);
+
+    unsafe impl<'a> Adapter for RunAdapter<'a> {
+        type Object = Task;
+        type Pointer = Pin<&'a Task>;
+
+        unsafe fn links(task: *const Task) -> *const Links {
+            ptr::addr_of!((*task).run)
+        }
+
+        unsafe fn object(links: *const Links) -> *const Task {
+            links.byte_sub(offset_of!(Task, run)).cast()
+        }
+    }
+
+    /// Links tasks by their `all` field, borrowing them.
+    struct AllAdapter<'a>(PhantomData<&'a ()>);
+
+    unsafe impl<'a> Adapter for AllAdapter<'a> {
+        type Object = Task;
+        type Pointer = Pin<&'a Task>;
+
+        unsafe fn links(task: *const Task) -> *const Links {
+            ptr::addr_of!((*task).all)
+        }
+
+        unsafe fn object(links: *const Links) -> *const Task {
+            links.byte_sub(offset_of!(Task, all)).cast()
+        }
+    }
+
+    /// Links tasks by their `run` field, owning them.
+    struct BoxAdapter;
+
+    unsafe impl Adapter for BoxAdapter {
+        type Object = Task;
+        type Pointer = Pin<Box<Task>>;
+
+        unsafe fn links(task: *const Task) -> *const Links {
+            ptr::addr_of!((*task).run)
+        }
+
+        unsafe fn object(links: *const Links) -> *const Task {
+            links.byte_sub(offset_of!(Task, run)).cast()
+        }
+    }
+
+    /// Links tasks by their `run` field, borrowing them mutably.
+    struct MutAdapter<'a>(PhantomData<&'a ()>);
+
+    unsafe impl<'a> Adapter for MutAdapter<'a> {
+        type Object = Task;
+        type Pointer = Pin<&'a mut Task>;
+
+        unsafe fn links(task: *const Task) -> *const Links {
+            ptr::addr_of!((*task).run)
+        }
+
+        unsafe fn object(links: *const Links) -> *const Task {
+            links.byte_sub(offset_of!(Task, run)).cast()
+        }
+    }
+
+    fn ids<'a>(iter: impl Iterator<Item = &'a Task>) -> Vec<u32> {
+        iter.map(|task| task.id).collect()
+    }
+
+    #[test]
+    fn basics() {
+        let mut list = pin!(IntrusiveList::<BoxAdapter>::new());
+        assert!(list.is_empty());
+        assert!(list.as_mut().pop_front().is_none());
+
+        list.as_mut().push_back(Box::pin(task(1)));
+        list.as_mut().push_back(Box::pin(task(2)));
+        list.as_mut().push_front(Box::pin(task(0)));
+        assert_eq!(list.front().unwrap().id, 0);
+        assert_eq!(list.back().unwrap().id, 2);
+        assert_eq!(format!("{:?}", list), "[0, 1, 2]");
+
+        let task = list.as_mut().pop_front().unwrap();
+        assert_eq!(task.id, 0);
+        assert!(!task.run.is_linked());
+        assert_eq!(list.as_mut().pop_back().unwrap().id, 2);
+        assert_eq!(list.as_mut().pop_back().unwrap().id, 1);
+        assert!(list.as_mut().pop_back().is_none());
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn several_lists() {
+        let tasks = [pin!(task(0)), pin!(task(1)), pin!(task(2)), pin!(task(3))];
+
+        let mut all = pin!(IntrusiveList::<AllAdapter>::new());
+        let mut run = pin!(IntrusiveList::<RunAdapter>::new());
+        for task in &tasks {
+            all.as_mut().push_back(task.as_ref());
+        }
+        run.as_mut().push_back(tasks[3].as_ref());
+        run.as_mut().push_back(tasks[1].as_ref());
+        assert_eq!(ids(all.iter()), [0, 1, 2, 3]);
+        assert_eq!(ids(run.iter()), [3, 1]);
+
+        // Removing a task from one list keeps it in the other.
+        let task = unsafe { run.as_mut().remove(&tasks[3]) };
+        assert_eq!(task.id, 3);
+        assert_eq!(ids(run.iter()), [1]);
+        assert_eq!(ids(all.iter()), [0, 1, 2, 3]);
+
+        // A task can also remove itself without knowing its list.
+        assert!(tasks[2].all.unlink());
+        assert!(!tasks[2].all.unlink());
+        assert_eq!(ids(all.iter()), [0, 1, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "object is already linked")]
+    fn already_linked() {
+        let task = pin!(task(0));
+        let mut list = pin!(IntrusiveList::<RunAdapter>::new());
+        list.as_mut().push_back(task.as_ref());
+        list.as_mut().push_back(task.as_ref());
+    }
+
+    #[test]
+    fn pinned_mut() {
+        let (mut a, mut b) = (pin!(task(0)), pin!(task(1)));
+        {
+            let mut list = pin!(IntrusiveList::<MutAdapter>::new());
+            list.as_mut().push_back(a.as_mut());
+            list.as_mut().push_front(b.as_mut());
+            assert_eq!(ids(list.iter()), [1, 0]);
+        }
+        // Dropping the list unlinks everything.
+        assert!(!a.run.is_linked());
+        assert!(!b.run.is_linked());
+    }
+
+    #[test]
+    fn drop_owned() {
+        thread_local! {
+            static DROPS: Cell<usize> = const { Cell::new(0) };
+        }
+
+        struct Counted(Task);
+
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                DROPS.with(|drops| drops.set(drops.get() + 1));
+            }
+        }
+
+        struct CountedAdapter;
+
+        unsafe impl Adapter for CountedAdapter {
+            type Object = Counted;
+            type Pointer = Pin<Box<Counted>>;
+
+            unsafe fn links(counted: *const Counted) -> *const Links {
+                ptr::addr_of!((*counted).0.run)
+            }
+
+            unsafe fn object(links: *const Links) -> *const Counted {
+                links
+                    .byte_sub(offset_of!(Counted, 0) + offset_of!(Task, run))
+                    .cast()
+            }
+        }
+
+        let mut list = Box::pin(IntrusiveList::<CountedAdapter>::new());
+        for id in 0..5 {
+            list.as_mut().push_back(Box::pin(Counted(task(id))));
+        }
+        drop(list.as_mut().pop_front());
+        assert_eq!(DROPS.with(Cell::get), 1);
+        drop(list);
+        assert_eq!(DROPS.with(Cell::get), 5);
+    }
+
+    #[test]
+    fn cursor() {
+        let mut list = pin!(IntrusiveList::<BoxAdapter>::new());
+        for id in 1..=4 {
+            list.as_mut().push_back(Box::pin(task(id)));
+        }
+
+        let mut cursor = list.as_mut().cursor_mut();
+        assert!(cursor.current().is_none());
+        assert_eq!(cursor.peek_next().unwrap().id, 1);
+        assert_eq!(cursor.peek_prev().unwrap().id, 4);
+
+        cursor.move_next();
+        assert_eq!(cursor.current().unwrap().id, 1);
+        assert!(cursor.peek_prev().is_none());
+        cursor.insert_before(Box::pin(task(0)));
+        cursor.insert_after(Box::pin(task(10)));
+        assert_eq!(cursor.peek_prev().unwrap().id, 0);
+        assert_eq!(cursor.peek_next().unwrap().id, 10);
+
+        cursor.move_next();
+        assert_eq!(cursor.remove_current().unwrap().id, 10);
+        assert_eq!(cursor.current().unwrap().id, 2);
+
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.move_prev();
+        assert!(cursor.current().is_none());
+        assert!(cursor.remove_current().is_none());
+        cursor.insert_after(Box::pin(task(100)));
+        cursor.insert_before(Box::pin(task(200)));
+        cursor.move_prev();
+        assert_eq!(cursor.current().unwrap().id, 200);
+
+        assert_eq!(ids(list.iter()), [100, 0, 1, 2, 3, 4, 200]);
+    }
+
+    #[test]
+    fn cursor_split_splice() {
+        let mut list = pin!(IntrusiveList::<BoxAdapter>::new());
+        let mut other = pin!(IntrusiveList::<BoxAdapter>::new());
+        for id in 0..6 {
+            list.as_mut().push_back(Box::pin(task(id)));
+        }
+        other.as_mut().push_back(Box::pin(task(10)));
+
+        let mut cursor = list.as_mut().cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        cursor.split_before(other.as_mut());
+        assert_eq!(cursor.current().unwrap().id, 1);
+        assert!(cursor.peek_prev().is_none());
+        cursor.split_after(other.as_mut());
+        assert!(cursor.peek_next().is_none());
+        assert_eq!(ids(list.iter()), [1]);
+        assert_eq!(ids(other.iter()), [2, 3, 4, 5, 10, 0]);
+
+        let mut cursor = list.as_mut().cursor_mut();
+        cursor.move_next();
+        cursor.splice_after(other.as_mut());
+        assert!(other.is_empty());
+        cursor.split_after(other.as_mut());
+        cursor.splice_before(other.as_mut());
+        assert_eq!(cursor.current().unwrap().id, 1);
+        assert_eq!(ids(list.iter()), [2, 3, 4, 5, 10, 0, 1]);
+
+        // At the ghost element, the whole list moves.
+        let mut cursor = list.as_mut().cursor_mut();
+        cursor.split_before(other.as_mut());
+        assert!(list.is_empty());
+        let mut cursor = list.as_mut().cursor_mut();
+        cursor.splice_after(other.as_mut());
+        cursor.split_after(other.as_mut());
+        cursor.splice_before(other.as_mut());
+        assert_eq!(ids(list.iter()), [2, 3, 4, 5, 10, 0, 1]);
+
+        // Splicing an empty list does nothing.
+        let mut cursor = list.as_mut().cursor_mut();
+        cursor.splice_before(other.as_mut());
+        cursor.splice_after(other.as_mut());
+        assert_eq!(ids(list.iter()), [2, 3, 4, 5, 10, 0, 1]);
+        assert!(other.is_empty());
+    }
+
+    #[test]
+    fn provenance() {
+        // Objects handed back by the list must be usable as a whole, and not
+        // only through their links.
+        let mut list = pin!(IntrusiveList::<BoxAdapter>::new());
+        for id in 0..3 {
+            list.as_mut().push_back(Box::pin(task(id)));
+        }
+        assert_eq!(ids(list.iter()), [0, 1, 2]);
+
+        let front: *const Task = list.front().unwrap();
+        let mut removed = unsafe { list.as_mut().remove(&*front) };
+        unsafe { removed.as_mut().get_unchecked_mut().id = 10 };
+        list.as_mut().push_back(removed);
+
+        let mut popped = list.as_mut().pop_front().unwrap();
+        unsafe { popped.as_mut().get_unchecked_mut().id += 10 };
+        assert_eq!(popped.id, 11);
+        assert_eq!(ids(list.iter()), [2, 10]);
+
+        let mut a = pin!(task(0));
+        {
+            let mut list = pin!(IntrusiveList::<MutAdapter>::new());
+            list.as_mut().push_back(a.as_mut());
+            let mut a = list.as_mut().pop_front().unwrap();
+            unsafe { a.as_mut().get_unchecked_mut().id = 1 };
+        }
+        assert_eq!(a.id, 1);
+    }
+}
@@ -0,0 +1,370 @@
+//! A persistent lazy stream, in the style of Haskell lists and Okasaki's
+//! streams.
+//!
+//! Like [`third::List`], a stream is a chain of shared nodes. Each node is
+//! only computed the first time it is looked at, and the result is memoized,
+//! so every clone of a stream sees the same elements and none of them is
+//! computed twice. Streams can be infinite, as long as only a finite prefix
+//! is forced.
+//!
+//! [`third::List`]: crate::third::List
+
+use std::{
+    cell::{Cell, OnceCell},
+    rc::Rc,
+};
+
+pub struct Stream<T> {
+    cell: Rc<Susp<T>>,
+}
+
+/// A memoized node, which is computed by `thunk` on first use.
+struct Susp<T> {
+    value: OnceCell<Value<T>>,
+    thunk: Cell<Option<Thunk<T>>>,
+}
+
+type Thunk<T> = Box<dyn FnOnce() -> Value<T>>;
+
+enum Value<T> {
+    Nil,
+    Cons(T, Stream<T>),
+    // The node evaluates to another stream, like `append` does once its
+    // first stream runs out.
+    Alias(Stream<T>),
+}
+
+impl<T> Stream<T> {
+    /// Creates an empty [`Stream`].
+    pub fn new() -> Self {
+        Stream::ready(Value::Nil)
+    }
+
+    /// Returns a stream made of `elem` followed by `tail`.
+    pub fn cons(elem: T, tail: Stream<T>) -> Self {
+        Stream::ready(Value::Cons(elem, tail))
+    }
+
+    /// Returns a stream that calls `f` to compute itself, the first time it
+    /// is forced.
+    pub fn lazy<F>(f: F) -> Self
+    where
+        F: FnOnce() -> Stream<T> + 'static,
+    {
+        Stream::suspend(move || Value::Alias(f()))
+    }
+
+    /// Returns a stream of the values returned by `f`, until it returns
+    /// `None`.
+    pub fn from_fn<F>(mut f: F) -> Self
+    where
+        T: 'static,
+        F: FnMut() -> Option<T> + 'static,
+    {
+        Stream::suspend(move || match f() {
+            Some(elem) => Value::Cons(elem, Stream::from_fn(f)),
+            None => Value::Nil,
+        })
+    }
+
+    /// Returns the infinite stream `init`, `f(init)`, `f(f(init))`, ...
+    pub fn iterate<F>(init: T, f: F) -> Self
+    where
+        T: Clone + 'static,
+        F: Fn(&T) -> T + 'static,
+    {
+        Stream::iterate_with(init, Rc::new(f))
+    }
+
+    fn iterate_with<F>(elem: T, f: Rc<F>) -> Self
+    where
+        T: Clone + 'static,
+        F: Fn(&T) -> T + 'static,
+    {
+        let prev = elem.clone();
+        let tail = Stream::suspend(move || Value::Alias(Stream::iterate_with(f(&prev), f)));
+        Stream::cons(elem, tail)
+    }
+
+    /// Returns the first element of the stream, or `None` if it is empty.
+    pub fn head(&self) -> Option<&T> {
+        self.force().map(|(elem, _)| elem)
+    }
+
+    /// Returns the stream without its first element.
+    pub fn tail(&self) -> Stream<T> {
+        self.force()
+            .map_or_else(Stream::new, |(_, tail)| tail.clone())
+    }
+
+    /// Returns `true` if the stream has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.force().is_none()
+    }
+
+    /// Returns an iterator over the stream, which forces it as it goes.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { next: Some(self) }
+    }
+
+    /// Returns a stream of the first `n` elements.
+    pub fn take(&self, n: usize) -> Stream<T>
+    where
+        T: Clone + 'static,
+    {
+        if n == 0 {
+            return Stream::new();
+        }
+        let source = self.clone();
+        Stream::suspend(move || match source.force() {
+            Some((elem, tail)) => Value::Cons(elem.clone(), tail.take(n - 1)),
+            None => Value::Nil,
+        })
+    }
+
+    /// Returns a stream of `f` applied to each element.
+    pub fn map<U, F>(&self, f: F) -> Stream<U>
+    where
+        T: 'static,
+        U: 'static,
+        F: Fn(&T) -> U + 'static,
+    {
+        self.map_with(Rc::new(f))
+    }
+
+    fn map_with<U, F>(&self, f: Rc<F>) -> Stream<U>
+    where
+        T: 'static,
+        U: 'static,
+        F: Fn(&T) -> U + 'static,
+    {
+        let source = self.clone();
+        Stream::suspend(move || match source.force() {
+            Some((elem, tail)) => Value::Cons(f(elem), tail.map_with(f)),
+            None => Value::Nil,
+        })
+    }
+
+    /// Returns a stream of the elements for which `predicate` returns
+    /// `true`.
+    ///
+    /// Forcing a node skips over the elements that are rejected, so
+    /// filtering an infinite stream that has no more matches never returns.
+    pub fn filter<P>(&self, predicate: P) -> Stream<T>
+    where
+        T: Clone + 'static,
+        P: Fn(&T) -> bool + 'static,
+    {
+        self.filter_with(Rc::new(predicate))
+    }
+
+    fn filter_with<P>(&self, predicate: Rc<P>) -> Stream<T>
+    where
+        T: Clone + 'static,
+        P: Fn(&T) -> bool + 'static,
+    {
+        let mut source = self.clone();
+        Stream::suspend(move || loop {
+            let tail = match source.force() {
+                Some((elem, tail)) if predicate(elem) => {
+                    return Value::Cons(elem.clone(), tail.filter_with(predicate));
+                }
+                Some((_, tail)) => tail.clone(),
+                None => return Value::Nil,
+            };
+            source = tail;
+        })
+    }
+
+    /// Returns a stream of pairs of elements of both streams, which is as
+    /// long as the shorter one.
+    pub fn zip<U>(&self, other: &Stream<U>) -> Stream<(T, U)>
+    where
+        T: Clone + 'static,
+        U: Clone + 'static,
+    {
+        let (a, b) = (self.clone(), other.clone());
+        Stream::suspend(move || match (a.force(), b.force()) {
+            (Some((x, a)), Some((y, b))) => Value::Cons((x.clone(), y.clone()), a.zip(b)),
+            _ => Value::Nil,
+        })
+    }
+
+    /// Returns a stream of the elements of this stream followed by the ones
+    /// of `other`, which is shared rather than copied.
+    pub fn append(&self, other: &Stream<T>) -> Stream<T>
+    where
+        T: Clone + 'static,
+    {
+        let (a, b) = (self.clone(), other.clone());
+        Stream::suspend(move || match a.force() {
+            Some((elem, tail)) => Value::Cons(elem.clone(), tail.append(&b)),
+            None => Value::Alias(b),
+        })
+    }
+
+    fn ready(value: Value<T>) -> Self {
+        Stream {
+            cell: Rc::new(Susp {
+                value: OnceCell::from(value),
+                thunk: Cell::new(None),
+            }),
+        }
+    }
+
+    fn suspend<F>(f: F) -> Self
+    where
+        F: FnOnce() -> Value<T> + 'static,
+    {
+        Stream {
+            cell: Rc::new(Susp {
+                value: OnceCell::new(),
+                thunk: Cell::new(Some(Box::new(f))),
+            }),
+        }
+    }
+
+    /// Evaluates the first node if needed, and returns its element and tail.
+    fn force(&self) -> Option<(&T, &Stream<T>)> {
+        let mut cell = &*self.cell;
+        loop {
+            let value = cell.value.get_or_init(|| {
+                let thunk = cell.thunk.take();
+                // The thunk is only missing if it panicked on an earlier try.
+                thunk.expect("stream forced after its thunk panicked")()
+            });
+            match value {
+                Value::Nil => return None,
+                Value::Cons(elem, tail) => return Some((elem, tail)),
+                Value::Alias(stream) => cell = &stream.cell,
+            }
+        }
+    }
+}
+
+impl<T> Default for Stream<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for Stream<T> {
+    /// Shares the nodes of the stream, which is O(1).
+    fn clone(&self) -> Self {
+        Stream {
+            cell: self.cell.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Stream<T> {
+    fn drop(&mut self) {
+        let mut next = take_tail(&mut self.cell);
+        while let Some(mut stream) = next {
+            next = take_tail(&mut stream.cell);
+        }
+    }
+}
+
+/// Detaches and returns the tail of `cell`, if nothing else refers to it.
+fn take_tail<T>(cell: &mut Rc<Susp<T>>) -> Option<Stream<T>> {
+    match Rc::get_mut(cell)?.value.take()? {
+        Value::Nil => None,
+        Value::Cons(_, tail) => Some(tail),
+        Value::Alias(stream) => Some(stream),
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Stream<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (elem, tail) = self.next?.force()?;
+        self.next = Some(tail);
+        Some(elem)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::Stream;
+
+    #[test]
+    fn basics() {
+        let stream = Stream::<i32>::new();
+        assert!(stream.is_empty());
+        assert_eq!(stream.head(), None);
+        assert!(stream.tail().is_empty());
+
+        let stream = Stream::cons(1, Stream::cons(2, Stream::new()));
+        assert_eq!(stream.head(), Some(&1));
+        assert_eq!(stream.tail().head(), Some(&2));
+        assert!(stream.tail().tail().is_empty());
+        assert_eq!(stream.iter().collect::<Vec<_>>(), [&1, &2]);
+    }
+
+    #[test]
+    fn forced_once() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+        let stream = Stream::from_fn(move || {
+            counter.set(counter.get() + 1);
+            (counter.get() <= 3).then(|| counter.get())
+        });
+        assert_eq!(calls.get(), 0);
+
+        let copy = stream.clone();
+        assert_eq!(stream.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(copy.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(stream.iter().count(), 3);
+        assert_eq!(calls.get(), 4);
+    }
+
+    #[test]
+    fn combinators() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+        let naturals = Stream::iterate(0u64, |n| n + 1);
+        let squares = naturals.map(move |n| {
+            counter.set(counter.get() + 1);
+            n * n
+        });
+        assert_eq!(calls.get(), 0);
+
+        let even = squares.filter(|n| n % 2 == 0);
+        let first = even.take(3);
+        assert_eq!(calls.get(), 0);
+        assert_eq!(first.iter().copied().collect::<Vec<_>>(), [0, 4, 16]);
+        assert_eq!(calls.get(), 5);
+
+        let pairs = naturals.zip(&first);
+        assert_eq!(
+            pairs.iter().cloned().collect::<Vec<_>>(),
+            [(0, 0), (1, 4), (2, 16)]
+        );
+
+        let both = first.append(&naturals);
+        assert_eq!(
+            both.take(5).iter().copied().collect::<Vec<_>>(),
+            [0, 4, 16, 0, 1]
+        );
+        assert_eq!(calls.get(), 5);
+
+        let lazy = Stream::lazy(|| Stream::cons(7, Stream::new()));
+        assert_eq!(Stream::new().append(&lazy).iter().collect::<Vec<_>>(), [&7]);
+    }
+
+    #[test]
+    fn long_stream() {
+        // Dropping a long forced stream must not overflow the stack.
+        let stream = Stream::iterate(0u64, |n| n + 1).take(1_000_000);
+        assert_eq!(stream.iter().count(), 1_000_000);
+        drop(stream);
+    }
+}
@@ -4,4 +4,20 @@ pub mod fourth;
 pub mod second;
 pub mod third;
 
+pub mod arena_list;
+pub mod async_queue;
+pub mod cache;
+pub mod concurrent;
+pub mod intrusive;
+pub mod lazy;
 pub mod linked_list;
+pub mod persistent;
+pub mod ring;
+pub mod skiplist;
+pub mod sync;
+pub mod unrolled;
+pub mod xor_list;
+
+mod util;
+
+pub mod viz;
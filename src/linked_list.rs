@@ -1,4 +1,15 @@
-use core::{fmt::Debug, hash::Hash, marker::PhantomData, mem, ptr::NonNull};
+use core::{
+    fmt::Debug,
+    hash::Hash,
+    iter::Rev,
+    marker::PhantomData,
+    mem,
+    ops::{Bound, Index, IndexMut, RangeBounds},
+    ptr::NonNull,
+};
+
+use crate::util::drop_all;
+use crate::viz::{Dot, Viz};
 
 pub struct LinkedList<T> {
     head: Option<NonNull<Node<T>>>,
@@ -7,7 +18,7 @@ pub struct LinkedList<T> {
     _marker: PhantomData<T>,
 }
 
-struct Node<T> {
+pub(crate) struct Node<T> {
     prev: Option<NonNull<Node<T>>>,
     next: Option<NonNull<Node<T>>>,
     elem: T,
@@ -26,48 +37,12 @@ impl<T> LinkedList<T> {
 
     /// Inserts an element at the beginning of the list.
     pub fn push_front(&mut self, elem: T) {
-        unsafe {
-            // Allocate the node at the heap, but we will manage the allocation.
-            let new_node = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
-                prev: None,
-                next: None,
-                elem,
-            })));
-
-            if let Some(old_head) = self.head {
-                (*old_head.as_ptr()).prev = Some(new_node);
-                (*new_node.as_ptr()).next = Some(old_head);
-            } else {
-                // Empty list case.
-                self.tail = Some(new_node);
-            }
-
-            self.head = Some(new_node);
-            self.len += 1;
-        }
+        self.push_front_node(elem);
     }
 
     /// Inserts an element at the back of the list.
     pub fn push_back(&mut self, elem: T) {
-        unsafe {
-            // Allocate the node at the heap, but we will manage the allocation.
-            let new_node = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
-                prev: None,
-                next: None,
-                elem,
-            })));
-
-            if let Some(old_tail) = self.tail {
-                (*old_tail.as_ptr()).next = Some(new_node);
-                (*new_node.as_ptr()).prev = Some(old_tail);
-            } else {
-                // Empty list case.
-                self.head = Some(new_node);
-            }
-
-            self.tail = Some(new_node);
-            self.len += 1;
-        }
+        self.push_back_node(elem);
     }
 
     /// Removes an element from the beginning of the list, and returns it.
@@ -148,6 +123,506 @@ impl<T> LinkedList<T> {
     pub fn clear(&mut self) {
         while self.pop_front().is_some() {}
     }
+
+    /// Returns a reference to the element at `index`, or `None` if it is out
+    /// of bounds.
+    ///
+    /// Walks O(min(index, len - index)) nodes.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len {
+            unsafe { Some(&(*self.node_at(index).as_ptr()).elem) }
+        } else {
+            None
+        }
+    }
+
+    /// Returns a mutable reference to the element at `index`, or `None` if it
+    /// is out of bounds.
+    ///
+    /// Walks O(min(index, len - index)) nodes.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.len {
+            unsafe { Some(&mut (*self.node_at(index).as_ptr()).elem) }
+        } else {
+            None
+        }
+    }
+
+    /// Inserts an element at `index`, shifting the elements after it
+    /// towards the back.
+    ///
+    /// Walks O(min(index, len - index)) nodes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the list, like
+    /// [`Vec::insert`].
+    pub fn insert(&mut self, index: usize, elem: T) {
+        assert!(
+            index <= self.len,
+            "insertion index (is {}) should be <= len (is {})",
+            index,
+            self.len
+        );
+        if index == self.len {
+            return self.push_back(elem);
+        }
+
+        unsafe {
+            let next = self.node_at(index);
+            let prev = (*next.as_ptr()).prev;
+            let new_node = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                prev,
+                next: Some(next),
+                elem,
+            })));
+
+            if let Some(prev) = prev {
+                (*prev.as_ptr()).next = Some(new_node);
+            } else {
+                self.head = Some(new_node);
+            }
+            (*next.as_ptr()).prev = Some(new_node);
+            self.len += 1;
+        }
+    }
+
+    /// Removes and returns the element at `index`, shifting the elements
+    /// after it towards the front.
+    ///
+    /// Walks O(min(index, len - index)) nodes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds, like [`Vec::remove`].
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(
+            index < self.len,
+            "removal index (is {}) should be < len (is {})",
+            index,
+            self.len
+        );
+        unsafe { self.remove_node(self.node_at(index)) }
+    }
+
+    /// Swaps the elements at indices `a` and `b`.
+    ///
+    /// Walks O(min(index, len - index)) nodes for each index. The elements
+    /// are swapped in place, and the nodes stay where they are.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` or `b` is out of bounds, like [`slice::swap`].
+    pub fn swap(&mut self, a: usize, b: usize) {
+        for index in [a, b] {
+            if index >= self.len {
+                index_out_of_bounds(index, self.len);
+            }
+        }
+        if a != b {
+            unsafe {
+                let (a, b) = (self.node_at(a), self.node_at(b));
+                mem::swap(&mut (*a.as_ptr()).elem, &mut (*b.as_ptr()).elem);
+            }
+        }
+    }
+
+    /// Returns `true` if the list contains an element equal to `x`.
+    pub fn contains(&self, x: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|elem| elem == x)
+    }
+
+    /// Returns the index of the first element matching `predicate`.
+    pub fn position<P: FnMut(&T) -> bool>(&self, predicate: P) -> Option<usize> {
+        self.iter().position(predicate)
+    }
+
+    /// Returns the index of the last element matching `predicate`, searching
+    /// from the back.
+    pub fn rposition<P: FnMut(&T) -> bool>(&self, predicate: P) -> Option<usize> {
+        self.iter().rposition(predicate)
+    }
+
+    /// Returns the first non-`None` result of `f` applied to the elements.
+    pub fn find_map<B, F: FnMut(&T) -> Option<B>>(&self, f: F) -> Option<B> {
+        self.iter().find_map(f)
+    }
+
+    /// Reverses the list in place, by swapping the links of every node.
+    pub fn reverse(&mut self) {
+        let mut cur = self.head;
+        unsafe {
+            while let Some(node) = cur {
+                let node = &mut *node.as_ptr();
+                mem::swap(&mut node.prev, &mut node.next);
+                cur = node.prev;
+            }
+        }
+        mem::swap(&mut self.head, &mut self.tail);
+    }
+
+    /// Returns a view of the list with its front and back swapped, without
+    /// touching the nodes.
+    pub fn reversed(&mut self) -> Reversed<'_, T> {
+        Reversed { list: self }
+    }
+
+    /// Rotates the list `n` places to the left, so the first `n` elements move
+    /// to the back, by relinking the head and the tail.
+    ///
+    /// Walks O(min(n, len - n)) nodes to find the new head.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than the length of the list, like
+    /// [`VecDeque::rotate_left`](std::collections::VecDeque::rotate_left).
+    pub fn rotate_left(&mut self, n: usize) {
+        assert!(
+            n <= self.len,
+            "rotation {} out of bounds for length {}",
+            n,
+            self.len
+        );
+        if n != 0 && n != self.len {
+            unsafe { self.rotate_to(self.node_at(n)) }
+        }
+    }
+
+    /// Rotates the list `n` places to the right, so the last `n` elements move
+    /// to the front, by relinking the head and the tail.
+    ///
+    /// Walks O(min(n, len - n)) nodes to find the new head.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than the length of the list, like
+    /// [`VecDeque::rotate_right`](std::collections::VecDeque::rotate_right).
+    pub fn rotate_right(&mut self, n: usize) {
+        assert!(
+            n <= self.len,
+            "rotation {} out of bounds for length {}",
+            n,
+            self.len
+        );
+        if n != 0 && n != self.len {
+            unsafe { self.rotate_to(self.node_at(self.len - n)) }
+        }
+    }
+
+    /// Moves the elements in `range` to just before the cursor `dest`, which
+    /// points into another list.
+    ///
+    /// The nodes are relinked rather than reallocated. Finding both ends of
+    /// the range walks from the nearer end of the list, and splicing is O(1).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds, like slicing does.
+    pub fn move_range_to<R: RangeBounds<usize>>(&mut self, range: R, dest: &mut CursorMut<'_, T>) {
+        let (start, end) = slice_range(range, self.len);
+        if start == end {
+            return;
+        }
+        unsafe {
+            let first = self.node_at(start);
+            let last = self.node_at(end - 1);
+            dest.splice_before(self.detach_range(first, last, end - start));
+        }
+    }
+
+    /// Returns the node at `index`, walking from the nearer end.
+    ///
+    /// # Safety
+    ///
+    /// `index` must be less than the length of the list.
+    unsafe fn node_at(&self, index: usize) -> NonNull<Node<T>> {
+        if index < self.len / 2 {
+            let mut node = self.head.unwrap();
+            for _ in 0..index {
+                node = (*node.as_ptr()).next.unwrap();
+            }
+            node
+        } else {
+            let mut node = self.tail.unwrap();
+            for _ in index + 1..self.len {
+                node = (*node.as_ptr()).prev.unwrap();
+            }
+            node
+        }
+    }
+
+    /// Makes `node` the head, moving the nodes before it to the back.
+    ///
+    /// # Safety
+    ///
+    /// `node` must belong to this list, and must not be its head.
+    unsafe fn rotate_to(&mut self, node: NonNull<Node<T>>) {
+        let (head, tail) = (self.head.unwrap(), self.tail.unwrap());
+        let new_tail = (*node.as_ptr()).prev.unwrap();
+
+        // Close the list into a ring, then cut it open before `node`.
+        (*tail.as_ptr()).next = Some(head);
+        (*head.as_ptr()).prev = Some(tail);
+        (*new_tail.as_ptr()).next = None;
+        (*node.as_ptr()).prev = None;
+
+        self.head = Some(node);
+        self.tail = Some(new_tail);
+    }
+
+    /// Detaches the `len` nodes from `first` to `last` into a new list.
+    ///
+    /// # Safety
+    ///
+    /// `first` and `last` must belong to this list, and `last` must come
+    /// `len - 1` nodes after `first`.
+    unsafe fn detach_range(
+        &mut self,
+        first: NonNull<Node<T>>,
+        last: NonNull<Node<T>>,
+        len: usize,
+    ) -> LinkedList<T> {
+        let prev = (*first.as_ptr()).prev.take();
+        let next = (*last.as_ptr()).next.take();
+
+        if let Some(prev) = prev {
+            (*prev.as_ptr()).next = next;
+        } else {
+            self.head = next;
+        }
+        if let Some(next) = next {
+            (*next.as_ptr()).prev = prev;
+        } else {
+            self.tail = prev;
+        }
+        self.len -= len;
+
+        LinkedList {
+            head: Some(first),
+            tail: Some(last),
+            len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Inserts an element at the beginning of the list, and returns its node.
+    pub(crate) fn push_front_node(&mut self, elem: T) -> NonNull<Node<T>> {
+        unsafe {
+            // Allocate the node at the heap, but we will manage the allocation.
+            let new_node = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                prev: None,
+                next: None,
+                elem,
+            })));
+
+            self.link_front(new_node);
+            new_node
+        }
+    }
+
+    /// Inserts an element at the back of the list, and returns its node.
+    pub(crate) fn push_back_node(&mut self, elem: T) -> NonNull<Node<T>> {
+        unsafe {
+            // Allocate the node at the heap, but we will manage the allocation.
+            let new_node = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                prev: None,
+                next: None,
+                elem,
+            })));
+
+            self.link_back(new_node);
+            new_node
+        }
+    }
+
+    /// Returns the first node of the list.
+    pub(crate) fn front_node(&self) -> Option<NonNull<Node<T>>> {
+        self.head
+    }
+
+    /// Moves a node of this list to the back of `other`.
+    ///
+    /// # Safety
+    ///
+    /// `node` must belong to this list.
+    pub(crate) unsafe fn move_node_to_back_of(
+        &mut self,
+        node: NonNull<Node<T>>,
+        other: &mut LinkedList<T>,
+    ) {
+        self.unlink(node);
+        other.link_back(node);
+    }
+
+    /// Moves a node of this list to the beginning of the list.
+    ///
+    /// # Safety
+    ///
+    /// `node` must belong to this list.
+    pub(crate) unsafe fn move_node_to_front(&mut self, node: NonNull<Node<T>>) {
+        if self.head != Some(node) {
+            self.unlink(node);
+            self.link_front(node);
+        }
+    }
+
+    /// Removes a node of this list, and returns its element.
+    ///
+    /// # Safety
+    ///
+    /// `node` must belong to this list.
+    pub(crate) unsafe fn remove_node(&mut self, node: NonNull<Node<T>>) -> T {
+        self.unlink(node);
+        Box::from_raw(node.as_ptr()).elem
+    }
+
+    /// Links a detached node at the beginning of the list.
+    unsafe fn link_front(&mut self, node: NonNull<Node<T>>) {
+        (*node.as_ptr()).prev = None;
+        (*node.as_ptr()).next = self.head;
+
+        if let Some(old_head) = self.head {
+            (*old_head.as_ptr()).prev = Some(node);
+        } else {
+            // Empty list case.
+            self.tail = Some(node);
+        }
+
+        self.head = Some(node);
+        self.len += 1;
+    }
+
+    /// Links a detached node at the back of the list.
+    unsafe fn link_back(&mut self, node: NonNull<Node<T>>) {
+        (*node.as_ptr()).prev = self.tail;
+        (*node.as_ptr()).next = None;
+
+        if let Some(old_tail) = self.tail {
+            (*old_tail.as_ptr()).next = Some(node);
+        } else {
+            // Empty list case.
+            self.head = Some(node);
+        }
+
+        self.tail = Some(node);
+        self.len += 1;
+    }
+
+    /// Links a detached node just before `next`, which belongs to the list.
+    unsafe fn link_before(&mut self, node: NonNull<Node<T>>, next: NonNull<Node<T>>) {
+        let prev = (*next.as_ptr()).prev;
+        (*node.as_ptr()).prev = prev;
+        (*node.as_ptr()).next = Some(next);
+
+        if let Some(prev) = prev {
+            (*prev.as_ptr()).next = Some(node);
+        } else {
+            self.head = Some(node);
+        }
+        (*next.as_ptr()).prev = Some(node);
+        self.len += 1;
+    }
+
+    /// Swaps a node with the one right after it, by relinking them.
+    ///
+    /// # Safety
+    ///
+    /// `a` must belong to this list, and `b` must be its next node.
+    unsafe fn swap_adjacent(&mut self, a: NonNull<Node<T>>, b: NonNull<Node<T>>) {
+        let prev = (*a.as_ptr()).prev;
+        let next = (*b.as_ptr()).next;
+
+        if let Some(prev) = prev {
+            (*prev.as_ptr()).next = Some(b);
+        } else {
+            self.head = Some(b);
+        }
+        if let Some(next) = next {
+            (*next.as_ptr()).prev = Some(a);
+        } else {
+            self.tail = Some(a);
+        }
+
+        (*b.as_ptr()).prev = prev;
+        (*b.as_ptr()).next = Some(a);
+        (*a.as_ptr()).prev = Some(b);
+        (*a.as_ptr()).next = next;
+    }
+
+    /// Detaches a node from the list, without freeing it.
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) {
+        let prev = (*node.as_ptr()).prev.take();
+        let next = (*node.as_ptr()).next.take();
+
+        if let Some(prev) = prev {
+            (*prev.as_ptr()).next = next;
+        } else {
+            self.head = next;
+        }
+        if let Some(next) = next {
+            (*next.as_ptr()).prev = prev;
+        } else {
+            self.tail = prev;
+        }
+
+        self.len -= 1;
+    }
+}
+
+#[cold]
+fn index_out_of_bounds(index: usize, len: usize) -> ! {
+    panic!(
+        "index out of bounds: the len is {} but the index is {}",
+        len, index
+    );
+}
+
+/// Converts `range` into start and end indices for a list of length `len`.
+///
+/// # Panics
+///
+/// Panics with the same messages as slicing if the range is out of bounds.
+fn slice_range<R: RangeBounds<usize>>(range: R, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start
+            .checked_add(1)
+            .expect("attempted to index slice from after maximum usize"),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end
+            .checked_add(1)
+            .expect("attempted to index slice up to maximum usize"),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+
+    if start > end {
+        panic!("slice index starts at {} but ends at {}", start, end);
+    }
+    if end > len {
+        panic!(
+            "range end index {} out of range for slice of length {}",
+            end, len
+        );
+    }
+    (start, end)
+}
+
+impl<T> Node<T> {
+    /// Returns a reference to the element of the node.
+    pub(crate) fn elem(&self) -> &T {
+        &self.elem
+    }
+
+    /// Returns a mutable reference to the element of the node.
+    pub(crate) fn elem_mut(&mut self) -> &mut T {
+        &mut self.elem
+    }
 }
 
 impl<T> Default for LinkedList<T> {
@@ -219,12 +694,72 @@ impl<T: Hash> Hash for LinkedList<T> {
     }
 }
 
+impl<T> Index<usize> for LinkedList<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        match self.get(index) {
+            Some(elem) => elem,
+            None => index_out_of_bounds(index, self.len),
+        }
+    }
+}
+
+impl<T> IndexMut<usize> for LinkedList<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len;
+        match self.get_mut(index) {
+            Some(elem) => elem,
+            None => index_out_of_bounds(index, len),
+        }
+    }
+}
+
+impl<T: Debug> Viz for LinkedList<T> {
+    fn viz(&self, name: &str, dot: &mut Dot) {
+        dot.entry(name, &format!("{}\nlen = {}", name, self.len));
+        dot.pointer(
+            name,
+            "head",
+            self.head.map(|head| head.as_ptr() as *const _),
+        );
+        dot.pointer(
+            name,
+            "tail",
+            self.tail.map(|tail| tail.as_ptr() as *const _),
+        );
+
+        let mut cur = self.head;
+        while let Some(node) = cur {
+            unsafe {
+                let node: *const Node<T> = node.as_ptr();
+                dot.node(node, &(*node).elem, None);
+                dot.edge(
+                    node,
+                    (*node).next.map(|next| next.as_ptr() as *const _),
+                    "next",
+                );
+                dot.edge(
+                    node,
+                    (*node).prev.map(|prev| prev.as_ptr() as *const _),
+                    "prev",
+                );
+                cur = (*node).next;
+            }
+        }
+    }
+}
+
 impl<T> Drop for LinkedList<T> {
     fn drop(&mut self) {
-        while self.pop_front().is_some() {}
+        drop_all(self, Self::pop_front);
     }
 }
 
+// The list owns its elements, so it is as thread-safe as they are.
+unsafe impl<T: Send> Send for LinkedList<T> {}
+unsafe impl<T: Sync> Sync for LinkedList<T> {}
+
 pub struct Iter<'a, T> {
     front: Option<NonNull<Node<T>>>,
     back: Option<NonNull<Node<T>>>,
@@ -241,6 +776,33 @@ impl<T> LinkedList<T> {
             _marker: PhantomData,
         }
     }
+
+    /// Returns an iterator over the elements in `range`.
+    ///
+    /// Finding each end of the range walks from the nearer end of the list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds, like slicing does.
+    pub fn range<R: RangeBounds<usize>>(&self, range: R) -> Iter<'_, T> {
+        let (start, end) = slice_range(range, self.len);
+        if start == end {
+            return Iter {
+                front: None,
+                back: None,
+                len: 0,
+                _marker: PhantomData,
+            };
+        }
+        unsafe {
+            Iter {
+                front: Some(self.node_at(start)),
+                back: Some(self.node_at(end - 1)),
+                len: end - start,
+                _marker: PhantomData,
+            }
+        }
+    }
 }
 
 impl<'a, T> IntoIterator for &'a LinkedList<T> {
@@ -293,6 +855,9 @@ impl<'a, T> ExactSizeIterator for Iter<'a, T> {
     }
 }
 
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
 pub struct IterMut<'a, T> {
     front: Option<NonNull<Node<T>>>,
     back: Option<NonNull<Node<T>>>,
@@ -309,6 +874,25 @@ impl<T> LinkedList<T> {
             _marker: PhantomData,
         }
     }
+
+    /// Returns a mutable iterator over the elements in `range`.
+    ///
+    /// Finding each end of the range walks from the nearer end of the list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds, like slicing does.
+    pub fn range_mut<R: RangeBounds<usize>>(&mut self, range: R) -> IterMut<'_, T> {
+        let Iter {
+            front, back, len, ..
+        } = self.range(range);
+        IterMut {
+            front,
+            back,
+            len,
+            _marker: PhantomData,
+        }
+    }
 }
 
 impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
@@ -361,6 +945,9 @@ impl<'a, T> ExactSizeIterator for IterMut<'a, T> {
     }
 }
 
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
 pub struct IntoIter<T> {
     list: LinkedList<T>,
 }
@@ -401,6 +988,66 @@ impl<T> DoubleEndedIterator for IntoIter<T> {
 
 impl<T> ExactSizeIterator for IntoIter<T> {}
 
+/// A draining iterator over a range of a [`LinkedList`], returned by
+/// [`LinkedList::drain`].
+pub struct Drain<'a, T> {
+    // The drained nodes are detached up front, so the list stays valid even
+    // if the iterator is leaked.
+    drained: LinkedList<T>,
+    _marker: PhantomData<&'a mut LinkedList<T>>,
+}
+
+impl<T> LinkedList<T> {
+    /// Removes the elements in `range` from the list, and returns them as an
+    /// iterator. `drain(..)` empties the list.
+    ///
+    /// The range is relinked out of the list before this returns. Elements
+    /// that are not consumed are dropped with the iterator, and leaking the
+    /// iterator only leaks them, leaving the list intact.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds, like [`Vec::drain`].
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T> {
+        let (start, end) = slice_range(range, self.len);
+        let drained = if start == end {
+            LinkedList::new()
+        } else if end - start == self.len {
+            mem::take(self)
+        } else {
+            unsafe {
+                let first = self.node_at(start);
+                let last = self.node_at(end - 1);
+                self.detach_range(first, last, end - start)
+            }
+        };
+        Drain {
+            drained,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Drain<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.drained.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.drained.len, Some(self.drained.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Drain<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.drained.pop_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Drain<'a, T> {}
+
 pub struct CursorMut<'a, T> {
     cur: Option<NonNull<Node<T>>>,
     list: &'a mut LinkedList<T>,
@@ -415,6 +1062,48 @@ impl<T> LinkedList<T> {
             index: None,
         }
     }
+
+    /// Returns a cursor at the first element matching `predicate`, or at the
+    /// ghost if there is none.
+    pub fn cursor_find_mut<P: FnMut(&T) -> bool>(&mut self, mut predicate: P) -> CursorMut<'_, T> {
+        let mut cur = self.head;
+        let mut index = 0;
+        unsafe {
+            while let Some(node) = cur {
+                if predicate(&(*node.as_ptr()).elem) {
+                    break;
+                }
+                cur = (*node.as_ptr()).next;
+                index += 1;
+            }
+        }
+        CursorMut {
+            cur,
+            list: self,
+            index: cur.map(|_| index),
+        }
+    }
+
+    /// Returns a cursor at the last element matching `predicate`, searching
+    /// from the back, or at the ghost if there is none.
+    pub fn cursor_rfind_mut<P: FnMut(&T) -> bool>(&mut self, mut predicate: P) -> CursorMut<'_, T> {
+        let mut cur = self.tail;
+        let mut index = self.len;
+        unsafe {
+            while let Some(node) = cur {
+                index -= 1;
+                if predicate(&(*node.as_ptr()).elem) {
+                    break;
+                }
+                cur = (*node.as_ptr()).prev;
+            }
+        }
+        CursorMut {
+            cur,
+            list: self,
+            index: cur.map(|_| index),
+        }
+    }
 }
 
 impl<'a, T> CursorMut<'a, T> {
@@ -488,6 +1177,17 @@ impl<'a, T> CursorMut<'a, T> {
         }
     }
 
+    /// Returns an iterator from the cursor to the back of the list, which is
+    /// empty at the ghost.
+    pub fn iter_from_here(&self) -> Iter<'_, T> {
+        Iter {
+            front: self.cur,
+            back: self.cur.and(self.list.tail),
+            len: self.index.map_or(0, |index| self.list.len - index),
+            _marker: PhantomData,
+        }
+    }
+
     /// Creates a new list by splitting the list before the cursor, returning the newly created list.
     /// The cursor will remain at the original list.
     pub fn split_before(&mut self) -> LinkedList<T> {
@@ -638,11 +1338,127 @@ impl<'a, T> CursorMut<'a, T> {
         }
     }
 
-    /// Removes the current element and returns it.
-    /// The cursor will be moved to the next element.
-    pub fn remove_current(&mut self) -> Option<T> {
-        if let Some(cur) = self.cur {
-            unsafe {
+    /// Removes the elements from the cursor up to, but not including, the one
+    /// at index `end`, and returns them as a new list.
+    /// The cursor will be moved to the element at `end`, or to the ghost if
+    /// `end` is the length of the list.
+    ///
+    /// Walks the O(k) removed nodes. At the ghost, `end` must be the length
+    /// of the list and nothing is removed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `end` is before the cursor or past the end of the list.
+    pub fn cut_range(&mut self, end: usize) -> LinkedList<T> {
+        let start = self.index.unwrap_or(self.list.len);
+        let (start, end) = slice_range(start..end, self.list.len);
+        if start == end {
+            return LinkedList::new();
+        }
+
+        unsafe {
+            let first = self.cur.unwrap();
+            let mut last = first;
+            for _ in start + 1..end {
+                last = (*last.as_ptr()).next.unwrap();
+            }
+
+            self.cur = (*last.as_ptr()).next;
+            if self.cur.is_none() {
+                self.index = None;
+            }
+            self.list.detach_range(first, last, end - start)
+        }
+    }
+
+    /// Swaps the current element with the next one, by relinking their nodes.
+    /// The cursor stays on the same element, which moves one place forward.
+    ///
+    /// Does nothing at the ghost or at the last element.
+    pub fn swap_with_next(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                if let Some(next) = (*cur.as_ptr()).next {
+                    self.list.swap_adjacent(cur, next);
+                    self.index = Some(self.index.unwrap() + 1);
+                }
+            }
+        }
+    }
+
+    /// Swaps the current element with the previous one, by relinking their
+    /// nodes. The cursor stays on the same element, which moves one place
+    /// back.
+    ///
+    /// Does nothing at the ghost or at the first element.
+    pub fn swap_with_prev(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                if let Some(prev) = (*cur.as_ptr()).prev {
+                    self.list.swap_adjacent(prev, cur);
+                    self.index = Some(self.index.unwrap() - 1);
+                }
+            }
+        }
+    }
+
+    /// Moves the current node to the front of the list. The cursor stays on
+    /// the same element.
+    ///
+    /// Does nothing at the ghost.
+    pub fn move_current_to_front(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe { self.list.move_node_to_front(cur) }
+            self.index = Some(0);
+        }
+    }
+
+    /// Moves the current node to the back of the list. The cursor stays on
+    /// the same element.
+    ///
+    /// Does nothing at the ghost.
+    pub fn move_current_to_back(&mut self) {
+        if let Some(cur) = self.cur {
+            if self.list.tail != Some(cur) {
+                unsafe {
+                    self.list.unlink(cur);
+                    self.list.link_back(cur);
+                }
+            }
+            self.index = Some(self.list.len - 1);
+        }
+    }
+
+    /// Moves the current node to just before the cursor `other`, which points
+    /// into another list, or to its back if `other` is at the ghost.
+    /// This cursor will be moved to the next element, and `other` stays on
+    /// its element.
+    ///
+    /// Does nothing at the ghost.
+    pub fn move_current_to(&mut self, other: &mut CursorMut<'_, T>) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                self.cur = (*cur.as_ptr()).next;
+                if self.cur.is_none() {
+                    self.index = None;
+                }
+                self.list.unlink(cur);
+
+                if let Some(next) = other.cur {
+                    other.list.link_before(cur, next);
+                    other.index = Some(other.index.unwrap() + 1);
+                } else {
+                    other.list.link_back(cur);
+                }
+            }
+        }
+    }
+
+    /// Removes the current element and returns it.
+    /// The cursor will be moved to the next element.
+    pub fn remove_current(&mut self) -> Option<T> {
+        if let Some(cur) = self.cur {
+            unsafe {
                 let boxed_node = Box::from_raw(cur.as_ptr());
 
                 boxed_node
@@ -675,9 +1491,222 @@ impl<'a, T> CursorMut<'a, T> {
     }
 }
 
+/// A view of a [`LinkedList`] with its front and back swapped, returned by
+/// [`LinkedList::reversed`].
+///
+/// Every operation maps directly to the opposite end of the list, so the
+/// view costs nothing.
+pub struct Reversed<'a, T> {
+    list: &'a mut LinkedList<T>,
+}
+
+impl<'a, T> Reversed<'a, T> {
+    /// Returns the length of the list.
+    pub fn len(&self) -> usize {
+        self.list.len
+    }
+
+    /// Returns `true` if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Returns the back element of the list.
+    pub fn front(&self) -> Option<&T> {
+        self.list.back()
+    }
+
+    /// Returns the back element of the list.
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.list.back_mut()
+    }
+
+    /// Returns the front element of the list.
+    pub fn back(&self) -> Option<&T> {
+        self.list.front()
+    }
+
+    /// Returns the front element of the list.
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.list.front_mut()
+    }
+
+    /// Inserts an element at the back of the list.
+    pub fn push_front(&mut self, elem: T) {
+        self.list.push_back(elem);
+    }
+
+    /// Inserts an element at the beginning of the list.
+    pub fn push_back(&mut self, elem: T) {
+        self.list.push_front(elem);
+    }
+
+    /// Removes the back element of the list.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.list.pop_back()
+    }
+
+    /// Removes the front element of the list.
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.list.pop_front()
+    }
+
+    /// Returns an iterator from the back of the list to the front.
+    pub fn iter(&self) -> Rev<Iter<'_, T>> {
+        self.list.iter().rev()
+    }
+
+    /// Returns a mutable iterator from the back of the list to the front.
+    pub fn iter_mut(&mut self) -> Rev<IterMut<'_, T>> {
+        self.list.iter_mut().rev()
+    }
+
+    /// Returns a cursor that moves from the back of the list to the front.
+    pub fn cursor_mut(&mut self) -> RevCursorMut<'_, T> {
+        RevCursorMut {
+            cursor: self.list.cursor_mut(),
+        }
+    }
+}
+
+/// A cursor over a [`Reversed`] view, which moves backwards through the list.
+///
+/// Indices count from the back of the list.
+pub struct RevCursorMut<'a, T> {
+    cursor: CursorMut<'a, T>,
+}
+
+impl<'a, T> RevCursorMut<'a, T> {
+    /// Retrieve a current index of the cursor, counted from the back.
+    pub fn index(&self) -> Option<usize> {
+        self.cursor
+            .index
+            .map(|index| self.cursor.list.len - 1 - index)
+    }
+
+    /// Move the cursor towards the front of the list.
+    pub fn move_next(&mut self) {
+        self.cursor.move_prev();
+    }
+
+    /// Move the cursor towards the back of the list.
+    pub fn move_prev(&mut self) {
+        self.cursor.move_next();
+    }
+
+    /// Retrieve an element at the cursor.
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.cursor.current()
+    }
+
+    /// Retrieve the element before the cursor in the list.
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        self.cursor.peek_prev()
+    }
+
+    /// Retrieve the element after the cursor in the list.
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        self.cursor.peek_next()
+    }
+
+    /// Removes the current element and returns it.
+    /// The cursor will be moved to the element before it in the list.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let elem = self.cursor.remove_current()?;
+        self.cursor.move_prev();
+        Some(elem)
+    }
+}
+
+/// Two halves of a [`LinkedList`] with a cursor each, returned by
+/// [`LinkedList::split_at_cursors`].
+///
+/// Each half is a list of its own while the split lasts, so neither cursor
+/// can walk past the boundary. Nodes can still be moved from one half to the
+/// other with [`CursorMut::move_current_to`], and the halves are joined back
+/// together, moved nodes included, when this is dropped. If it is leaked
+/// instead, the list is left empty and the elements are leaked with it.
+pub struct SplitCursors<'a, T> {
+    list: &'a mut LinkedList<T>,
+    front: LinkedList<T>,
+    back: LinkedList<T>,
+}
+
+impl<T> LinkedList<T> {
+    /// Splits the list before index `at`, so that both halves can be edited
+    /// through their own cursor at the same time.
+    ///
+    /// Walks O(min(at, len - at)) nodes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is greater than the length of the list.
+    pub fn split_at_cursors(&mut self, at: usize) -> SplitCursors<'_, T> {
+        assert!(
+            at <= self.len,
+            "`at` split index (is {}) should be <= len (is {})",
+            at,
+            self.len
+        );
+        let mut front = mem::take(self);
+        let back = if at == front.len {
+            LinkedList::new()
+        } else {
+            unsafe {
+                let first = front.node_at(at);
+                let last = front.tail.unwrap();
+                let len = front.len - at;
+                front.detach_range(first, last, len)
+            }
+        };
+        SplitCursors {
+            list: self,
+            front,
+            back,
+        }
+    }
+}
+
+impl<'a, T> SplitCursors<'a, T> {
+    /// Returns a cursor over the front half and a cursor over the back half,
+    /// both starting at the ghost of their half.
+    ///
+    /// The indices of the back cursor count from the start of the back half.
+    pub fn cursors(&mut self) -> (CursorMut<'_, T>, CursorMut<'_, T>) {
+        (self.front.cursor_mut(), self.back.cursor_mut())
+    }
+}
+
+impl<'a, T> Drop for SplitCursors<'a, T> {
+    fn drop(&mut self) {
+        let mut list = mem::take(&mut self.front);
+        list.cursor_mut().splice_before(mem::take(&mut self.back));
+        *self.list = list;
+    }
+}
+
+impl<'a, T: Debug> Viz for CursorMut<'a, T> {
+    fn viz(&self, name: &str, dot: &mut Dot) {
+        self.list.viz(name, dot);
+        dot.ghost(
+            name,
+            self.list.head.map(|head| head.as_ptr() as *const Node<T>),
+            self.list.tail.map(|tail| tail.as_ptr() as *const Node<T>),
+        );
+        dot.cursor(
+            name,
+            self.cur.map(|cur| cur.as_ptr() as *const Node<T>),
+            self.index,
+        );
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use std::mem;
+
     use super::LinkedList;
+    use crate::util::test::{assert_send_sync, panics, Drops};
 
     fn generate_test() -> LinkedList<i32> {
         list_from(&[0, 1, 2, 3, 4, 5, 6])
@@ -1072,6 +2101,658 @@ mod test {
         );
     }
 
+    #[test]
+    fn rotate() {
+        use std::collections::VecDeque;
+
+        for len in 0..8 {
+            for n in 0..=len {
+                let mut list: LinkedList<i32> = (0..len as i32).collect();
+                let mut deque: VecDeque<i32> = (0..len as i32).collect();
+                list.rotate_left(n);
+                deque.rotate_left(n);
+                check_links(&list);
+                assert!(list.iter().eq(&deque), "rotate_left({}) of {}", n, len);
+
+                list.rotate_right(n);
+                deque.rotate_right(n);
+                check_links(&list);
+                assert!(list.iter().eq(&deque), "rotate_right({}) of {}", n, len);
+                assert_eq!(list.len(), len);
+            }
+        }
+
+        // Both ends still work after relinking.
+        let mut list = generate_test();
+        list.rotate_right(2);
+        list.push_back(7);
+        list.push_front(-1);
+        assert_eq!(list.pop_back(), Some(7));
+        assert_eq!(list.pop_front(), Some(-1));
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), [5, 6, 0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "rotation 8 out of bounds for length 7")]
+    fn rotate_out_of_bounds() {
+        generate_test().rotate_left(8);
+    }
+
+    #[test]
+    fn cut_range() {
+        let mut list = generate_test();
+        let mut cursor = list.cursor_mut();
+        for _ in 0..3 {
+            cursor.move_next();
+        }
+
+        let cut = cursor.cut_range(5);
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 5));
+        assert!(cursor.cut_range(2).is_empty());
+
+        let rest = cursor.cut_range(4);
+        assert_eq!(cursor.index(), None);
+        assert!(cursor.cut_range(2).is_empty());
+
+        check_links(&cut);
+        check_links(&rest);
+        check_links(&list);
+        assert_eq!(cut.len(), 3);
+        assert_eq!(cut.into_iter().collect::<Vec<_>>(), [2, 3, 4]);
+        assert_eq!(rest.into_iter().collect::<Vec<_>>(), [5, 6]);
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), [0, 1]);
+
+        // Cut everything.
+        let mut list = generate_test();
+        let mut cursor = list.cursor_mut();
+        cursor.move_next();
+        let all = cursor.cut_range(7);
+        assert_eq!(all.len(), 7);
+        assert!(list.is_empty());
+        check_links(&list);
+        list.push_back(1);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), [1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "slice index starts at 3 but ends at 1")]
+    fn cut_range_backwards() {
+        let mut list = generate_test();
+        let mut cursor = list.cursor_mut();
+        for _ in 0..4 {
+            cursor.move_next();
+        }
+        cursor.cut_range(1);
+    }
+
+    #[test]
+    fn move_range_to() {
+        let mut list = generate_test();
+        let mut dest = list_from(&[10, 11]);
+
+        let mut cursor = dest.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        list.move_range_to(2..5, &mut cursor);
+        assert_eq!(cursor.index(), Some(4));
+        assert_eq!(cursor.current(), Some(&mut 11));
+
+        // At the ghost, the range is appended.
+        cursor.move_next();
+        list.move_range_to(..=0, &mut cursor);
+        list.move_range_to(1..1, &mut cursor);
+        assert_eq!(cursor.index(), None);
+
+        check_links(&list);
+        check_links(&dest);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), [1, 5, 6]);
+        assert_eq!(dest.len(), 6);
+        assert_eq!(dest.into_iter().collect::<Vec<_>>(), [10, 2, 3, 4, 11, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "range end index 8 out of range for slice of length 7")]
+    fn move_range_out_of_bounds() {
+        let mut dest = LinkedList::new();
+        generate_test().move_range_to(2..8, &mut dest.cursor_mut());
+    }
+
+    #[test]
+    fn indexing() {
+        let mut list = generate_test();
+        let mut vec: Vec<i32> = (0..7).collect();
+        assert_eq!(list.get(0), Some(&0));
+        assert_eq!(list.get(6), Some(&6));
+        assert_eq!(list.get(7), None);
+        assert_eq!(list[4], 4);
+
+        *list.get_mut(5).unwrap() = 50;
+        list[1] = 10;
+        vec[5] = 50;
+        vec[1] = 10;
+        assert_eq!(list.get_mut(7), None);
+
+        for (index, elem) in [(0, -1), (8, 8), (3, 30), (6, 60)] {
+            list.insert(index, elem);
+            vec.insert(index, elem);
+        }
+        check_links(&list);
+        assert!(list.iter().eq(&vec));
+
+        for index in [0, 8, 3, 4] {
+            assert_eq!(list.remove(index), vec.remove(index));
+        }
+        check_links(&list);
+        assert!(list.iter().eq(&vec));
+
+        for (a, b) in [(0, 6), (2, 3), (4, 4), (5, 1)] {
+            list.swap(a, b);
+            vec.swap(a, b);
+        }
+        check_links(&list);
+        assert!(list.iter().eq(&vec));
+        assert_eq!(list.len(), vec.len());
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds: the len is 7 but the index is 7")]
+    fn index_out_of_bounds() {
+        let _ = generate_test()[7];
+    }
+
+    #[test]
+    #[should_panic(expected = "insertion index (is 8) should be <= len (is 7)")]
+    fn insert_out_of_bounds() {
+        generate_test().insert(8, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "removal index (is 7) should be < len (is 7)")]
+    fn remove_out_of_bounds() {
+        generate_test().remove(7);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds: the len is 7 but the index is 9")]
+    fn swap_out_of_bounds() {
+        generate_test().swap(1, 9);
+    }
+
+    #[test]
+    fn search() {
+        let mut list = list_from(&[3, 1, 4, 1, 5, 9, 2, 6]);
+        assert!(list.contains(&9));
+        assert!(!list.contains(&7));
+        assert_eq!(list.position(|&x| x == 1), Some(1));
+        assert_eq!(list.rposition(|&x| x == 1), Some(3));
+        assert_eq!(list.position(|&x| x > 10), None);
+        assert_eq!(list.rposition(|&x| x > 10), None);
+        assert_eq!(list.find_map(|&x| (x > 4).then(|| x * 10)), Some(50));
+        assert_eq!(list.find_map(|&x| (x > 10).then_some(x)), None);
+
+        let mut cursor = list.cursor_find_mut(|&x| x == 1);
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(cursor.remove_current(), Some(1));
+        assert_eq!(cursor.current(), Some(&mut 4));
+
+        let mut cursor = list.cursor_rfind_mut(|&x| x < 3);
+        assert_eq!(cursor.index(), Some(5));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        cursor.splice_after(list_from(&[7, 8]));
+
+        let mut cursor = list.cursor_find_mut(|&x| x > 10);
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.current(), None);
+        let cursor = list.cursor_rfind_mut(|&x| x > 10);
+        assert_eq!(cursor.index(), None);
+
+        check_links(&list);
+        assert_eq!(
+            list.into_iter().collect::<Vec<_>>(),
+            [3, 4, 1, 5, 9, 2, 7, 8, 6]
+        );
+    }
+
+    #[test]
+    fn reverse() {
+        let mut list: LinkedList<i32> = LinkedList::new();
+        list.reverse();
+        assert!(list.is_empty());
+
+        let mut list = generate_test();
+        list.reverse();
+        check_links(&list);
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            [6, 5, 4, 3, 2, 1, 0]
+        );
+        list.push_back(-1);
+        list.push_front(7);
+        list.reverse();
+        check_links(&list);
+        assert_eq!(
+            list.into_iter().collect::<Vec<_>>(),
+            [-1, 0, 1, 2, 3, 4, 5, 6, 7]
+        );
+    }
+
+    #[test]
+    fn reversed() {
+        let mut list = generate_test();
+        let mut view = list.reversed();
+        assert_eq!(view.len(), 7);
+        assert_eq!(view.front(), Some(&6));
+        assert_eq!(view.back(), Some(&0));
+        assert_eq!(
+            view.iter().copied().collect::<Vec<_>>(),
+            [6, 5, 4, 3, 2, 1, 0]
+        );
+
+        view.push_front(7);
+        view.push_back(-1);
+        assert_eq!(view.pop_front(), Some(7));
+        *view.back_mut().unwrap() = -2;
+        *view.front_mut().unwrap() = 60;
+        for elem in view.iter_mut() {
+            *elem *= 10;
+        }
+
+        let mut cursor = view.cursor_mut();
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 600));
+        assert_eq!(cursor.peek_next(), Some(&mut 50));
+        assert_eq!(cursor.peek_prev(), None);
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(50));
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(cursor.current(), Some(&mut 40));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.index(), None);
+
+        // Removing the last element in view order moves to the ghost.
+        let mut cursor = view.cursor_mut();
+        cursor.move_prev();
+        assert_eq!(cursor.index(), Some(6));
+        assert_eq!(cursor.remove_current(), Some(-20));
+        assert_eq!(cursor.index(), None);
+
+        check_links(&list);
+        assert_eq!(
+            list.into_iter().collect::<Vec<_>>(),
+            [0, 10, 20, 30, 40, 600]
+        );
+    }
+
+    #[test]
+    fn drain() {
+        let mut list = generate_test();
+        let mut drain = list.drain(2..5);
+        assert_eq!(drain.len(), 3);
+        assert_eq!(drain.next(), Some(2));
+        assert_eq!(drain.next_back(), Some(4));
+        drop(drain);
+        check_links(&list);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [0, 1, 5, 6]);
+
+        assert_eq!(list.drain(1..1).count(), 0);
+        assert_eq!(list.drain(3..).collect::<Vec<_>>(), [6]);
+        assert_eq!(list.drain(..=0).collect::<Vec<_>>(), [0]);
+        check_links(&list);
+
+        // Draining everything leaves the list empty but usable.
+        assert_eq!(list.drain(..).rev().collect::<Vec<_>>(), [5, 1]);
+        assert!(list.is_empty());
+        list.push_back(7);
+        list.push_front(6);
+        check_links(&list);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [6, 7]);
+    }
+
+    #[test]
+    // Leaks on purpose.
+    #[cfg_attr(miri, ignore)]
+    fn drain_forget() {
+        let mut list = generate_test();
+        mem::forget(list.drain(1..6));
+        check_links(&list);
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), [0, 6]);
+    }
+
+    #[test]
+    fn drain_drop_panic() {
+        let drops = Drops::default();
+        let mut list = LinkedList::new();
+        for i in 0..6 {
+            list.push_back(drops.elem(i == 3));
+        }
+
+        assert!(panics(|| {
+            let mut drain = list.drain(1..5);
+            drain.next();
+        }));
+        assert_eq!(drops.count(), 4);
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "range end index 9 out of range for slice of length 7")]
+    fn drain_out_of_bounds() {
+        generate_test().drain(3..9);
+    }
+
+    #[test]
+    fn range() {
+        let mut list = generate_test();
+        assert_eq!(list.range(2..5).copied().collect::<Vec<_>>(), [2, 3, 4]);
+        assert_eq!(
+            list.range(..).rev().copied().collect::<Vec<_>>(),
+            [6, 5, 4, 3, 2, 1, 0]
+        );
+        assert_eq!(list.range(3..3).next(), None);
+        assert_eq!(list.range(7..).next_back(), None);
+
+        let mut iter = list.range(1..=5);
+        assert_eq!(iter.len(), 5);
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&5));
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.collect::<Vec<_>>(), [&2, &3, &4]);
+
+        for elem in list.range_mut(4..) {
+            *elem *= 10;
+        }
+        let mut iter = list.range_mut(..2);
+        assert_eq!(iter.next_back(), Some(&mut 1));
+        assert_eq!(iter.next_back(), Some(&mut 0));
+        assert_eq!(iter.next_back(), None);
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            [0, 1, 2, 3, 40, 50, 60]
+        );
+
+        let mut cursor = list.cursor_mut();
+        assert_eq!(cursor.iter_from_here().next(), None);
+        for _ in 0..5 {
+            cursor.move_next();
+        }
+        let iter = cursor.iter_from_here();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.rev().collect::<Vec<_>>(), [&60, &50, &40]);
+    }
+
+    #[test]
+    #[should_panic(expected = "slice index starts at 5 but ends at 2")]
+    fn range_backwards() {
+        #[allow(clippy::reversed_empty_ranges)]
+        generate_test().range(5..2);
+    }
+
+    #[test]
+    fn split_at_cursors() {
+        // Merge the two sorted halves of the list, using a read cursor on the
+        // back half and a write cursor on the front half.
+        let mut list = list_from(&[1, 4, 6, 9, 2, 3, 7, 8, 10]);
+        {
+            let mut split = list.split_at_cursors(4);
+            let (mut write, mut read) = split.cursors();
+            write.move_next();
+            read.move_next();
+            assert_eq!(write.index(), Some(0));
+            assert_eq!(read.index(), Some(0));
+
+            while let Some(&mut elem) = read.current() {
+                match write.current() {
+                    Some(cur) if *cur <= elem => write.move_next(),
+                    _ => {
+                        read.remove_current();
+                        write.splice_before(Some(elem).into_iter().collect());
+                    }
+                }
+            }
+            assert_eq!(write.index(), None);
+            assert_eq!(read.index(), None);
+        }
+        check_links(&list);
+        assert_eq!(list.len(), 9);
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            [1, 2, 3, 4, 6, 7, 8, 9, 10]
+        );
+
+        // A cursor cannot leave its half.
+        let mut split = list.split_at_cursors(3);
+        let (mut front, mut back) = split.cursors();
+        front.move_prev();
+        assert_eq!(front.current(), Some(&mut 3));
+        assert_eq!(front.peek_next(), None);
+        back.move_next();
+        assert_eq!(back.current(), Some(&mut 4));
+        assert_eq!(back.peek_prev(), None);
+        assert_eq!(front.split_after().len(), 0);
+        assert_eq!(back.split_before().len(), 0);
+        drop(split);
+        check_links(&list);
+        assert_eq!(list.len(), 9);
+
+        // Splitting at either end leaves one half empty.
+        let mut split = list.split_at_cursors(0);
+        assert_eq!(split.cursors().0.peek_next(), None);
+        drop(split);
+        let mut split = list.split_at_cursors(9);
+        assert_eq!(split.cursors().1.peek_next(), None);
+        drop(split);
+        assert_eq!(list.len(), 9);
+    }
+
+    #[test]
+    fn split_at_cursors_interleaved() {
+        // Both cursors are used in turn, with references into both halves
+        // alive at once, and nodes moved across the boundary both ways.
+        let mut list = generate_test();
+        let addresses: Vec<_> = list.iter().map(|elem| elem as *const i32).collect();
+        {
+            let mut split = list.split_at_cursors(4);
+            let (mut front, mut back) = split.cursors();
+            front.move_next();
+            back.move_next();
+            let (a, b) = (front.current().unwrap(), back.current().unwrap());
+            *a += 10;
+            *b += 10;
+            assert_eq!((*a, *b), (10, 14));
+
+            // Move 14 to the front half, before 10, then 1 to the back half.
+            back.move_current_to(&mut front);
+            assert_eq!(back.current(), Some(&mut 5));
+            assert_eq!(front.index(), Some(1));
+            assert_eq!(front.peek_prev(), Some(&mut 14));
+            front.move_next();
+            front.move_current_to(&mut back);
+            assert_eq!(front.current(), Some(&mut 2));
+            assert_eq!(back.index(), Some(1));
+            assert_eq!(back.peek_prev(), Some(&mut 1));
+
+            // Move the whole back half to the back of the front half.
+            front.move_next();
+            front.move_next();
+            assert_eq!(front.index(), None);
+            back.move_prev();
+            while back.current().is_some() {
+                back.move_current_to(&mut front);
+                *front.peek_prev().unwrap() += 100;
+            }
+            assert_eq!(back.peek_next(), None);
+        }
+        check_links(&list);
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            [14, 10, 2, 3, 101, 105, 106]
+        );
+        let mut moved: Vec<_> = list.iter().map(|elem| elem as *const i32).collect();
+        moved.sort();
+        let mut expected = addresses;
+        expected.sort();
+        assert_eq!(moved, expected);
+    }
+
+    #[test]
+    // Leaks on purpose.
+    #[cfg_attr(miri, ignore)]
+    fn split_at_cursors_forget() {
+        // Leaking the split leaves the list empty, but valid.
+        let mut list = generate_test();
+        mem::forget(list.split_at_cursors(5));
+        assert!(list.is_empty());
+        list.push_back(1);
+        check_links(&list);
+    }
+
+    #[test]
+    #[should_panic(expected = "`at` split index (is 8) should be <= len (is 7)")]
+    fn split_at_cursors_out_of_bounds() {
+        generate_test().split_at_cursors(8);
+    }
+
+    #[test]
+    fn swap_with_next() {
+        // Bubble sort by relinking nodes.
+        let mut list = list_from(&[5, 1, 4, 2, 8, 0, 3]);
+        let addresses: Vec<_> = list.iter().map(|elem| elem as *const i32).collect();
+        for _ in 0..list.len() {
+            let mut cursor = list.cursor_mut();
+            cursor.move_next();
+            while let Some(&mut elem) = cursor.current() {
+                match cursor.peek_next() {
+                    Some(next) if *next < elem => {
+                        let index = cursor.index().unwrap();
+                        cursor.swap_with_next();
+                        assert_eq!(cursor.index(), Some(index + 1));
+                        assert_eq!(cursor.current().copied(), Some(elem));
+                    }
+                    _ => cursor.move_next(),
+                }
+            }
+        }
+        check_links(&list);
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            [0, 1, 2, 3, 4, 5, 8]
+        );
+
+        // The elements were not moved in memory.
+        let mut sorted: Vec<_> = list.iter().map(|elem| elem as *const i32).collect();
+        let mut original = addresses;
+        sorted.sort();
+        original.sort();
+        assert_eq!(sorted, original);
+
+        let mut cursor = list.cursor_mut();
+        cursor.swap_with_next();
+        cursor.swap_with_prev();
+        cursor.move_next();
+        cursor.swap_with_prev();
+        assert_eq!(cursor.index(), Some(0));
+        cursor.swap_with_prev();
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.swap_with_next();
+        assert_eq!(cursor.index(), Some(6));
+        cursor.swap_with_prev();
+        assert_eq!(cursor.index(), Some(5));
+        assert_eq!(cursor.current(), Some(&mut 8));
+        check_links(&list);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), [0, 1, 2, 3, 4, 8, 5]);
+    }
+
+    #[test]
+    fn move_current() {
+        let mut list = generate_test();
+        let mut cursor = list.cursor_mut();
+        cursor.move_current_to_front();
+        cursor.move_current_to_back();
+        assert_eq!(cursor.index(), None);
+
+        for _ in 0..4 {
+            cursor.move_next();
+        }
+        cursor.move_current_to_front();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 3));
+        cursor.move_current_to_front();
+        assert_eq!(cursor.index(), Some(0));
+
+        cursor.move_next();
+        cursor.move_current_to_back();
+        assert_eq!(cursor.index(), Some(6));
+        assert_eq!(cursor.current(), Some(&mut 0));
+        cursor.move_current_to_back();
+        assert_eq!(cursor.index(), Some(6));
+        assert_eq!(cursor.peek_next(), None);
+        check_links(&list);
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            [3, 1, 2, 4, 5, 6, 0]
+        );
+
+        let mut other = list_from(&[10, 11]);
+        let mut dest = other.cursor_mut();
+        dest.move_next();
+        dest.move_next();
+
+        let mut cursor = list.cursor_mut();
+        cursor.move_next();
+        cursor.move_current_to(&mut dest);
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(dest.index(), Some(2));
+        assert_eq!(dest.current(), Some(&mut 11));
+
+        // At the ghost, the node goes to the back.
+        dest.move_next();
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.move_current_to(&mut dest);
+        assert_eq!(cursor.index(), None);
+        assert_eq!(dest.index(), None);
+        cursor.move_current_to(&mut dest);
+
+        check_links(&list);
+        check_links(&other);
+        assert_eq!(list.len(), 5);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), [1, 2, 4, 5, 6]);
+        assert_eq!(other.len(), 4);
+        assert_eq!(other.into_iter().collect::<Vec<_>>(), [10, 3, 11, 0]);
+    }
+
+    #[test]
+    fn send_sync() {
+        assert_send_sync::<LinkedList<i32>>();
+        assert_send_sync::<super::Iter<'_, i32>>();
+        assert_send_sync::<super::IterMut<'_, i32>>();
+        assert_send_sync::<super::Drain<'_, i32>>();
+    }
+
+    #[test]
+    fn drop_panic() {
+        let drops = Drops::default();
+        let mut list = LinkedList::new();
+        list.push_back(drops.elem(false));
+        list.push_back(drops.elem(false));
+        list.push_back(drops.elem(true));
+        list.push_back(drops.elem(false));
+        list.push_front(drops.elem(false));
+        list.push_front(drops.elem(false));
+
+        assert!(panics(move || drop(list)));
+        assert_eq!(drops.count(), 6);
+    }
+
     fn check_links<T: Eq + std::fmt::Debug>(list: &LinkedList<T>) {
         let from_front: Vec<_> = list.iter().collect();
         let from_back: Vec<_> = list.iter().rev().collect();
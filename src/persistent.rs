@@ -0,0 +1,1068 @@
+//! Persistent collections built on top of the shared-node [`third::List`].
+//!
+//! Every operation returns a new version of the collection and leaves the old
+//! one untouched. Versions share all the nodes they have in common, so cloning
+//! is O(1) and keeping old versions around is cheap.
+//!
+//! [`third::List`]: crate::third::List
+
+use std::{
+    cell::{Cell, OnceCell},
+    fmt,
+    iter::FromIterator,
+    rc::Rc,
+    vec,
+};
+
+use crate::third::{self, List};
+
+/// A persistent FIFO queue, made of a front list and a reversed rear list.
+///
+/// Elements are pushed onto the rear list, and popped off the front list.
+/// When the front runs out, the rear list is reversed to become the new
+/// front. Each element is moved at most once, so a sequence of operations
+/// on the newest version is amortized O(1).
+///
+/// The amortization does not survive persistence: popping the same old
+/// version over and over redoes its reversal every time. Use
+/// [`BankersQueue`] when old versions are reused.
+pub struct Queue<T> {
+    // Invariant: `front` is only empty when the whole queue is.
+    front: List<Rc<T>>,
+    rear: List<Rc<T>>,
+    len: usize,
+}
+
+impl<T> Queue<T> {
+    /// Creates an empty [`Queue`].
+    pub fn new() -> Self {
+        Queue {
+            front: List::new(),
+            rear: List::new(),
+            len: 0,
+        }
+    }
+
+    /// Returns the number of elements in the queue.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the queue contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the front element, or `None` if the queue is empty.
+    pub fn front(&self) -> Option<&T> {
+        self.front.head().map(|elem| &**elem)
+    }
+
+    /// Returns a new queue with `elem` added to the back.
+    pub fn push_back(&self, elem: T) -> Self {
+        Queue::check(
+            self.front.clone(),
+            self.rear.prepend(Rc::new(elem)),
+            self.len + 1,
+        )
+    }
+
+    /// Returns the front element and a new queue without it, or `None` if
+    /// the queue is empty.
+    pub fn pop_front(&self) -> Option<(&T, Self)> {
+        let elem = self.front.head()?;
+        let queue = Queue::check(self.front.tail(), self.rear.clone(), self.len - 1);
+        Some((elem, queue))
+    }
+
+    /// Returns an iterator over the queue, from front to back.
+    ///
+    /// Like [`pop_front`](Queue::pop_front), the iterator reverses the rear
+    /// list once it gets there, which takes O(n) time and memory.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(
+            Elems::List(self.front.iter()),
+            Elems::List(self.rear.iter()),
+            self.len,
+        )
+    }
+
+    /// Restores the invariant by moving the rear list to the front if the
+    /// front has run out.
+    fn check(front: List<Rc<T>>, rear: List<Rc<T>>, len: usize) -> Self {
+        if front.head().is_some() {
+            return Queue { front, rear, len };
+        }
+        let front = rear
+            .iter()
+            .fold(List::new(), |front, elem| front.prepend(elem.clone()));
+        Queue {
+            front,
+            rear: List::new(),
+            len,
+        }
+    }
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for Queue<T> {
+    /// Shares the nodes of the queue, which is O(1).
+    fn clone(&self) -> Self {
+        Queue {
+            front: self.front.clone(),
+            rear: self.rear.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> FromIterator<T> for Queue<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter()
+            .fold(Queue::new(), |queue, elem| queue.push_back(elem))
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Queue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for Queue<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for Queue<T> {}
+
+impl<'a, T> IntoIterator for &'a Queue<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A persistent FIFO queue whose operations are O(1) in the worst case, even
+/// when old versions are reused.
+///
+/// Like the banker's queue, it rebalances as soon as the rear list grows
+/// longer than the front, by lazily appending the reversed rear to the front.
+/// The rotation is a chain of memoized suspensions that each move a single
+/// element, and every operation forces one suspension of a schedule ahead of
+/// the front. By the time a suspension is reached by `pop_front` it has
+/// already been forced, so no operation does more than a constant amount of
+/// work, and versions that share a suspension share its result.
+pub struct BankersQueue<T> {
+    front: Stream<T>,
+    rear: List<Rc<T>>,
+    // The part of `front` that has not been forced yet. Its length is the
+    // length of `front` minus the length of `rear`.
+    schedule: Stream<T>,
+    len: usize,
+}
+
+type Stream<T> = Rc<Susp<T>>;
+
+/// A memoized cell of a lazy stream.
+struct Susp<T> {
+    value: OnceCell<Option<Cons<T>>>,
+    thunk: Cell<Option<Thunk<T>>>,
+}
+
+struct Cons<T> {
+    elem: Rc<T>,
+    next: Stream<T>,
+}
+
+/// A suspended step of building a stream, which does O(1) work when forced,
+/// as long as the streams it reads from have been forced by the schedule.
+enum Thunk<T> {
+    /// `front ++ reverse(rear) ++ acc`, where `rear` is one element longer
+    /// than `front`. Moves one element of each per step.
+    Rotate {
+        front: Stream<T>,
+        rear: List<Rc<T>>,
+        acc: Stream<T>,
+    },
+    /// The first `n` elements of `stream`.
+    Take { n: usize, stream: Stream<T> },
+    /// `front ++ reverse(rear) ++ acc`, where `rear` is about [`BALANCE`]
+    /// times longer than `front`. Moves one element of `front` and
+    /// [`BALANCE`] of `rear` per step.
+    RotateRev {
+        front: Stream<T>,
+        rear: Stream<T>,
+        acc: Stream<T>,
+    },
+    /// `front ++ reverse(drop(skip, rear))`. Skips [`BALANCE`] elements of
+    /// `rear` per step, then finishes like `RotateRev`.
+    RotateDrop {
+        front: Stream<T>,
+        skip: usize,
+        rear: Stream<T>,
+    },
+}
+
+/// How much longer than the other one an end of a [`Deque`] may grow before
+/// rebalancing.
+const BALANCE: usize = 3;
+
+#[cfg(test)]
+thread_local! {
+    /// The number of suspensions forced so far on this thread.
+    static FORCED: Cell<usize> = const { Cell::new(0) };
+}
+
+impl<T> Susp<T> {
+    fn ready(value: Option<Cons<T>>) -> Stream<T> {
+        Rc::new(Susp {
+            value: OnceCell::from(value),
+            thunk: Cell::new(None),
+        })
+    }
+
+    fn lazy(thunk: Thunk<T>) -> Stream<T> {
+        Rc::new(Susp {
+            value: OnceCell::new(),
+            thunk: Cell::new(Some(thunk)),
+        })
+    }
+
+    /// Evaluates the cell on first use, and returns the memoized result.
+    fn force(&self) -> Option<&Cons<T>> {
+        self.value
+            .get_or_init(|| {
+                #[cfg(test)]
+                FORCED.with(|forced| forced.set(forced.get() + 1));
+                let thunk = self.thunk.take();
+                thunk.expect("unforced cell has no thunk").eval()
+            })
+            .as_ref()
+    }
+}
+
+impl<T> Clone for Cons<T> {
+    fn clone(&self) -> Self {
+        Cons {
+            elem: self.elem.clone(),
+            next: self.next.clone(),
+        }
+    }
+}
+
+impl<T> Thunk<T> {
+    fn eval(self) -> Option<Cons<T>> {
+        match self {
+            Thunk::Rotate { front, rear, acc } => {
+                let last = rear.head().expect("rotation ran out of rear").clone();
+                let acc = Some(Cons {
+                    elem: last,
+                    next: acc,
+                });
+                match front.force() {
+                    None => acc,
+                    Some(cons) => Some(Cons {
+                        elem: cons.elem.clone(),
+                        next: Susp::lazy(Thunk::Rotate {
+                            front: cons.next.clone(),
+                            rear: rear.tail(),
+                            acc: Susp::ready(acc),
+                        }),
+                    }),
+                }
+            }
+            Thunk::Take { n: 0, .. } => None,
+            Thunk::Take { n, stream } => stream.force().map(|cons| Cons {
+                elem: cons.elem.clone(),
+                next: Susp::lazy(Thunk::Take {
+                    n: n - 1,
+                    stream: cons.next.clone(),
+                }),
+            }),
+            Thunk::RotateRev { front, rear, acc } => match front.force() {
+                None => reverse_onto(&rear, usize::MAX, acc).force().cloned(),
+                Some(cons) => Some(Cons {
+                    elem: cons.elem.clone(),
+                    next: Susp::lazy(Thunk::RotateRev {
+                        front: cons.next.clone(),
+                        rear: skip(&rear, BALANCE),
+                        acc: reverse_onto(&rear, BALANCE, acc),
+                    }),
+                }),
+            },
+            Thunk::RotateDrop {
+                front,
+                skip: n,
+                rear,
+            } => match front.force() {
+                Some(cons) if n >= BALANCE => Some(Cons {
+                    elem: cons.elem.clone(),
+                    next: Susp::lazy(Thunk::RotateDrop {
+                        front: cons.next.clone(),
+                        skip: n - BALANCE,
+                        rear: skip(&rear, BALANCE),
+                    }),
+                }),
+                _ => Thunk::RotateRev {
+                    front,
+                    rear: skip(&rear, n),
+                    acc: Susp::ready(None),
+                }
+                .eval(),
+            },
+        }
+    }
+}
+
+/// Returns `stream` without its first `n` elements.
+fn skip<T>(stream: &Stream<T>, n: usize) -> Stream<T> {
+    let mut stream = stream.clone();
+    for _ in 0..n {
+        match stream.force() {
+            Some(cons) => stream = cons.next.clone(),
+            None => break,
+        }
+    }
+    stream
+}
+
+/// Returns the first `n` elements of `stream` reversed, followed by `acc`.
+fn reverse_onto<T>(stream: &Stream<T>, n: usize, mut acc: Stream<T>) -> Stream<T> {
+    let mut next = stream.force();
+    for _ in 0..n {
+        let Some(cons) = next else { break };
+        acc = Susp::ready(Some(Cons {
+            elem: cons.elem.clone(),
+            next: acc,
+        }));
+        next = cons.next.force();
+    }
+    acc
+}
+
+impl<T> Drop for Susp<T> {
+    fn drop(&mut self) {
+        let mut next = self.value.take().flatten().map(|cons| cons.next);
+        while let Some(susp) = next {
+            if let Ok(mut susp) = Rc::try_unwrap(susp) {
+                next = susp.value.take().flatten().map(|cons| cons.next);
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+impl<T> BankersQueue<T> {
+    /// Creates an empty [`BankersQueue`].
+    pub fn new() -> Self {
+        let empty = Susp::ready(None);
+        BankersQueue {
+            front: empty.clone(),
+            rear: List::new(),
+            schedule: empty,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of elements in the queue.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the queue contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the front element, or `None` if the queue is empty.
+    pub fn front(&self) -> Option<&T> {
+        self.front.force().map(|cons| &*cons.elem)
+    }
+
+    /// Returns a new queue with `elem` added to the back.
+    pub fn push_back(&self, elem: T) -> Self {
+        BankersQueue::exec(
+            self.front.clone(),
+            self.rear.prepend(Rc::new(elem)),
+            &self.schedule,
+            self.len + 1,
+        )
+    }
+
+    /// Returns the front element and a new queue without it, or `None` if
+    /// the queue is empty.
+    pub fn pop_front(&self) -> Option<(&T, Self)> {
+        let cons = self.front.force()?;
+        let queue = BankersQueue::exec(
+            cons.next.clone(),
+            self.rear.clone(),
+            &self.schedule,
+            self.len - 1,
+        );
+        Some((&cons.elem, queue))
+    }
+
+    /// Returns an iterator over the queue, from front to back.
+    ///
+    /// Unlike the other operations, this is not O(1) per element: the rear
+    /// list is only reversed lazily by the rotations, so the iterator buffers
+    /// it in O(n) time and memory once it gets there.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(
+            Elems::Stream(self.front.force()),
+            Elems::List(self.rear.iter()),
+            self.len,
+        )
+    }
+
+    /// Forces the next suspension of the schedule, or starts a new rotation
+    /// once the schedule has run out, which is when the rear has become one
+    /// element longer than the front.
+    fn exec(front: Stream<T>, rear: List<Rc<T>>, schedule: &Stream<T>, len: usize) -> Self {
+        match schedule.force() {
+            Some(cons) => BankersQueue {
+                front,
+                rear,
+                schedule: cons.next.clone(),
+                len,
+            },
+            None => {
+                let front = Susp::lazy(Thunk::Rotate {
+                    front,
+                    rear,
+                    acc: Susp::ready(None),
+                });
+                BankersQueue {
+                    front: front.clone(),
+                    rear: List::new(),
+                    schedule: front,
+                    len,
+                }
+            }
+        }
+    }
+}
+
+impl<T> Default for BankersQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for BankersQueue<T> {
+    /// Shares the nodes of the queue, which is O(1).
+    fn clone(&self) -> Self {
+        BankersQueue {
+            front: self.front.clone(),
+            rear: self.rear.clone(),
+            schedule: self.schedule.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> FromIterator<T> for BankersQueue<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter()
+            .fold(BankersQueue::new(), |queue, elem| queue.push_back(elem))
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for BankersQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for BankersQueue<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for BankersQueue<T> {}
+
+impl<'a, T> IntoIterator for &'a BankersQueue<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A persistent double-ended queue whose operations are O(1) in the worst
+/// case, even when old versions are reused.
+///
+/// This is Okasaki's real-time deque. Both ends are lazy streams, the rear one
+/// stored back to front, and neither may grow more than three times longer
+/// than the other. When one does, half of the elements are moved to the other
+/// end by a lazy rotation. As in [`BankersQueue`], each end has a schedule of
+/// the suspensions that have not been forced yet, and every operation forces
+/// a few of them, so that no rotation step is left to do by the time it is
+/// reached.
+pub struct Deque<T> {
+    // Invariant: neither end is more than three times as long as the other,
+    // plus one. In particular, an end is only empty if the other one has at
+    // most one element.
+    front: Stream<T>,
+    rear: Stream<T>,
+    front_len: usize,
+    rear_len: usize,
+    // The parts of `front` and `rear` that have not been forced yet.
+    front_schedule: Stream<T>,
+    rear_schedule: Stream<T>,
+}
+
+impl<T> Deque<T> {
+    /// Creates an empty [`Deque`].
+    pub fn new() -> Self {
+        let empty = Susp::ready(None);
+        Deque {
+            front: empty.clone(),
+            rear: empty.clone(),
+            front_len: 0,
+            rear_len: 0,
+            front_schedule: empty.clone(),
+            rear_schedule: empty,
+        }
+    }
+
+    /// Returns the number of elements in the deque.
+    pub fn len(&self) -> usize {
+        self.front_len + self.rear_len
+    }
+
+    /// Returns `true` if the deque contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the front element, or `None` if the deque is empty.
+    pub fn front(&self) -> Option<&T> {
+        // If the front is empty, the rear has at most one element.
+        self.front
+            .force()
+            .or_else(|| self.rear.force())
+            .map(|cons| &*cons.elem)
+    }
+
+    /// Returns the back element, or `None` if the deque is empty.
+    pub fn back(&self) -> Option<&T> {
+        self.rear
+            .force()
+            .or_else(|| self.front.force())
+            .map(|cons| &*cons.elem)
+    }
+
+    /// Returns a new deque with `elem` added to the front.
+    pub fn push_front(&self, elem: T) -> Self {
+        let front = Susp::ready(Some(Cons {
+            elem: Rc::new(elem),
+            next: self.front.clone(),
+        }));
+        Deque::check(
+            front,
+            self.front_len + 1,
+            skip(&self.front_schedule, 1),
+            self.rear.clone(),
+            self.rear_len,
+            skip(&self.rear_schedule, 1),
+        )
+    }
+
+    /// Returns a new deque with `elem` added to the back.
+    pub fn push_back(&self, elem: T) -> Self {
+        let rear = Susp::ready(Some(Cons {
+            elem: Rc::new(elem),
+            next: self.rear.clone(),
+        }));
+        Deque::check(
+            self.front.clone(),
+            self.front_len,
+            skip(&self.front_schedule, 1),
+            rear,
+            self.rear_len + 1,
+            skip(&self.rear_schedule, 1),
+        )
+    }
+
+    /// Returns the front element and a new deque without it, or `None` if
+    /// the deque is empty.
+    pub fn pop_front(&self) -> Option<(&T, Self)> {
+        match self.front.force() {
+            Some(cons) => {
+                let deque = Deque::check(
+                    cons.next.clone(),
+                    self.front_len - 1,
+                    skip(&self.front_schedule, 2),
+                    self.rear.clone(),
+                    self.rear_len,
+                    skip(&self.rear_schedule, 2),
+                );
+                Some((&cons.elem, deque))
+            }
+            None => self.rear.force().map(|cons| (&*cons.elem, Deque::new())),
+        }
+    }
+
+    /// Returns the back element and a new deque without it, or `None` if
+    /// the deque is empty.
+    pub fn pop_back(&self) -> Option<(&T, Self)> {
+        match self.rear.force() {
+            Some(cons) => {
+                let deque = Deque::check(
+                    self.front.clone(),
+                    self.front_len,
+                    skip(&self.front_schedule, 2),
+                    cons.next.clone(),
+                    self.rear_len - 1,
+                    skip(&self.rear_schedule, 2),
+                );
+                Some((&cons.elem, deque))
+            }
+            None => self.front.force().map(|cons| (&*cons.elem, Deque::new())),
+        }
+    }
+
+    /// Returns an iterator over the deque, from front to back.
+    ///
+    /// The rear is stored back to front, so the iterator buffers it in O(n)
+    /// time and memory once it gets there.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(
+            Elems::Stream(self.front.force()),
+            Elems::Stream(self.rear.force()),
+            self.len(),
+        )
+    }
+
+    /// Restores the invariant by starting a rotation that moves half of the
+    /// elements to the other end, if one end has grown too long.
+    fn check(
+        front: Stream<T>,
+        front_len: usize,
+        front_schedule: Stream<T>,
+        rear: Stream<T>,
+        rear_len: usize,
+        rear_schedule: Stream<T>,
+    ) -> Self {
+        let len = front_len + rear_len;
+        let (front, rear, front_len) = if front_len > BALANCE * rear_len + 1 {
+            let (front, rear) = Deque::rotate(front, rear, len / 2);
+            (front, rear, len / 2)
+        } else if rear_len > BALANCE * front_len + 1 {
+            let (rear, front) = Deque::rotate(rear, front, len / 2);
+            (front, rear, len - len / 2)
+        } else {
+            return Deque {
+                front,
+                rear,
+                front_len,
+                rear_len,
+                front_schedule,
+                rear_schedule,
+            };
+        };
+        Deque {
+            front_schedule: front.clone(),
+            rear_schedule: rear.clone(),
+            front,
+            rear,
+            front_len,
+            rear_len: len - front_len,
+        }
+    }
+
+    /// Lazily keeps the first `keep` elements of the `long` end, and moves
+    /// the others to the back of the `short` one.
+    fn rotate(long: Stream<T>, short: Stream<T>, keep: usize) -> (Stream<T>, Stream<T>) {
+        let kept = Susp::lazy(Thunk::Take {
+            n: keep,
+            stream: long.clone(),
+        });
+        let short = Susp::lazy(Thunk::RotateDrop {
+            front: short,
+            skip: keep,
+            rear: long,
+        });
+        (kept, short)
+    }
+}
+
+impl<T> Default for Deque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for Deque<T> {
+    /// Shares the nodes of the deque, which is O(1).
+    fn clone(&self) -> Self {
+        Deque {
+            front: self.front.clone(),
+            rear: self.rear.clone(),
+            front_len: self.front_len,
+            rear_len: self.rear_len,
+            front_schedule: self.front_schedule.clone(),
+            rear_schedule: self.rear_schedule.clone(),
+        }
+    }
+}
+
+impl<T> FromIterator<T> for Deque<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter()
+            .fold(Deque::new(), |deque, elem| deque.push_back(elem))
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Deque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for Deque<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for Deque<T> {}
+
+impl<'a, T> IntoIterator for &'a Deque<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the elements of a [`Queue`], a [`BankersQueue`] or a
+/// [`Deque`].
+///
+/// The front is walked in place. The rear is stored back to front, so it is
+/// collected into a buffer and reversed when the front runs out, which costs
+/// O(n) time and memory once, and nothing if iteration stops early.
+pub struct Iter<'a, T> {
+    front: Elems<'a, T>,
+    // Taken and reversed into `reversed` once the front runs out.
+    rear: Option<Elems<'a, T>>,
+    reversed: vec::IntoIter<&'a T>,
+    len: usize,
+}
+
+/// The elements of a list or of a stream, in the order they are stored.
+enum Elems<'a, T> {
+    List(third::Iter<'a, Rc<T>>),
+    Stream(Option<&'a Cons<T>>),
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn new(front: Elems<'a, T>, rear: Elems<'a, T>, len: usize) -> Self {
+        Iter {
+            front,
+            rear: Some(rear),
+            reversed: Vec::new().into_iter(),
+            len,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let elem = self.front.next().or_else(|| {
+            if let Some(rear) = self.rear.take() {
+                let mut reversed: Vec<&T> = rear.collect();
+                reversed.reverse();
+                self.reversed = reversed.into_iter();
+            }
+            self.reversed.next()
+        })?;
+        self.len -= 1;
+        Some(elem)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> Iterator for Elems<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Elems::List(iter) => iter.next().map(|elem| &**elem),
+            Elems::Stream(next) => next.take().map(|cons| {
+                *next = cons.next.force();
+                &*cons.elem
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::Cell, collections::VecDeque};
+
+    use super::{BankersQueue, Deque, Queue, FORCED};
+
+    #[test]
+    fn queue() {
+        let empty = Queue::new();
+        assert_eq!(empty.front(), None);
+        assert!(empty.pop_front().is_none());
+
+        let one = empty.push_back(1);
+        let three = one.push_back(2).push_back(3);
+        assert_eq!(three.len(), 3);
+        assert_eq!(three.iter().collect::<Vec<_>>(), [&1, &2, &3]);
+
+        let (elem, two) = three.pop_front().unwrap();
+        assert_eq!(elem, &1);
+        assert_eq!(two.iter().copied().collect::<Vec<_>>(), [2, 3]);
+
+        // Older versions are unaffected.
+        assert!(empty.is_empty());
+        assert_eq!(one.iter().copied().collect::<Vec<_>>(), [1]);
+        assert_eq!(three.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+
+        let four = two.push_back(4);
+        assert_eq!(format!("{:?}", four), "[2, 3, 4]");
+        assert_eq!(four, [2, 3, 4].into_iter().collect());
+        assert_eq!(four.iter().len(), 3);
+    }
+
+    #[test]
+    fn bankers_queue() {
+        let empty = BankersQueue::new();
+        assert_eq!(empty.front(), None);
+        assert!(empty.pop_front().is_none());
+
+        let one = empty.push_back(1);
+        let three = one.push_back(2).push_back(3);
+        assert_eq!(three.len(), 3);
+        assert_eq!(three.iter().collect::<Vec<_>>(), [&1, &2, &3]);
+
+        let (elem, two) = three.pop_front().unwrap();
+        assert_eq!(elem, &1);
+        assert_eq!(two.iter().copied().collect::<Vec<_>>(), [2, 3]);
+
+        assert!(empty.is_empty());
+        assert_eq!(one.iter().copied().collect::<Vec<_>>(), [1]);
+        assert_eq!(three.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+
+        let four = two.push_back(4);
+        assert_eq!(format!("{:?}", four), "[2, 3, 4]");
+        assert_eq!(four, [2, 3, 4].into_iter().collect());
+        assert_eq!(four.iter().len(), 3);
+    }
+
+    #[test]
+    fn versions() {
+        // Branch off random older versions, and check each one against a
+        // `VecDeque` that was cloned along with it.
+        let mut versions = vec![(Queue::new(), BankersQueue::new(), VecDeque::new())];
+        let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+        for i in 0..2000 {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            let (queue, bankers, model) = &versions[seed as usize % versions.len()];
+            let (mut queue, mut bankers, mut model) =
+                (queue.clone(), bankers.clone(), model.clone());
+            if seed.is_multiple_of(3) {
+                if let Some((elem, rest)) = queue.pop_front() {
+                    assert_eq!(Some(elem), model.front());
+                    queue = rest;
+                }
+                if let Some((elem, rest)) = bankers.pop_front() {
+                    assert_eq!(Some(elem), model.front());
+                    bankers = rest;
+                }
+                model.pop_front();
+            } else {
+                queue = queue.push_back(i);
+                bankers = bankers.push_back(i);
+                model.push_back(i);
+            }
+            assert_eq!(queue.len(), model.len());
+            assert_eq!(bankers.len(), model.len());
+            versions.push((queue, bankers, model));
+        }
+        for (queue, bankers, model) in &versions {
+            assert!(queue.iter().eq(model));
+            assert!(bankers.iter().eq(model));
+        }
+    }
+
+    #[test]
+    fn deque() {
+        let empty = Deque::new();
+        assert_eq!(empty.front(), None);
+        assert_eq!(empty.back(), None);
+        assert!(empty.pop_front().is_none());
+        assert!(empty.pop_back().is_none());
+
+        let one = empty.push_back(2);
+        assert_eq!(one.front(), Some(&2));
+        assert_eq!(one.back(), Some(&2));
+        assert!(one.pop_front().unwrap().1.is_empty());
+        assert!(one.pop_back().unwrap().1.is_empty());
+
+        let three = one.push_front(1).push_back(3);
+        assert_eq!(three.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+
+        let (elem, two) = three.pop_back().unwrap();
+        assert_eq!(elem, &3);
+        let (elem, one_more) = two.pop_front().unwrap();
+        assert_eq!(elem, &1);
+        let two = one_more.push_back(4);
+        assert_eq!(format!("{:?}", two), "[2, 4]");
+        assert_eq!(two, [2, 4].into_iter().collect());
+        assert_eq!(two.iter().len(), 2);
+
+        // Older versions are unaffected.
+        assert!(empty.is_empty());
+        assert_eq!(one.iter().copied().collect::<Vec<_>>(), [2]);
+        assert_eq!(three.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn deque_versions() {
+        let mut versions = vec![(Deque::new(), VecDeque::new())];
+        let mut seed = 0x9e37_79b9_7f4a_7c15_u64;
+        for i in 0..2000 {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            let (deque, model) = &versions[seed as usize % versions.len()];
+            let (mut deque, mut model) = (deque.clone(), model.clone());
+            match seed % 5 {
+                0 => {
+                    if let Some((elem, rest)) = deque.pop_front() {
+                        assert_eq!(Some(elem), model.front());
+                        deque = rest;
+                    }
+                    model.pop_front();
+                }
+                1 => {
+                    if let Some((elem, rest)) = deque.pop_back() {
+                        assert_eq!(Some(elem), model.back());
+                        deque = rest;
+                    }
+                    model.pop_back();
+                }
+                2 | 3 => {
+                    deque = deque.push_front(i);
+                    model.push_front(i);
+                }
+                _ => {
+                    deque = deque.push_back(i);
+                    model.push_back(i);
+                }
+            }
+            assert_eq!(deque.len(), model.len());
+            assert_eq!(deque.front(), model.front());
+            assert_eq!(deque.back(), model.back());
+            versions.push((deque, model));
+        }
+        for (deque, model) in &versions {
+            assert!(deque.iter().eq(model));
+        }
+    }
+
+    #[test]
+    fn deque_reuse() {
+        // Build a history where rotations start before the previous ones have
+        // finished, then pop every version of it over and over. Each pop forces
+        // at most two suspensions of each schedule, however often a version is
+        // reused.
+        let forced = || FORCED.with(Cell::get);
+        let mut versions = vec![(Deque::new(), VecDeque::new())];
+        for i in 0..3000 {
+            let (deque, model) = versions.last().unwrap();
+            let (mut deque, mut model) = (deque.clone(), model.clone());
+            if i % 7 < 2 {
+                deque = deque.pop_back().map_or(deque.clone(), |(_, rest)| rest);
+                model.pop_back();
+            } else {
+                deque = deque.push_front(i);
+                model.push_front(i);
+            }
+            versions.push((deque, model));
+        }
+        for (deque, model) in &versions {
+            for _ in 0..3 {
+                let before = forced();
+                let popped = deque.pop_front();
+                assert!(forced() - before <= 4);
+                assert_eq!(popped.map(|(elem, _)| elem), model.front());
+
+                let before = forced();
+                let popped = deque.pop_back();
+                assert!(forced() - before <= 4);
+                assert_eq!(popped.map(|(elem, _)| elem), model.back());
+            }
+        }
+    }
+
+    #[test]
+    fn undo() {
+        // Keep every state of a buffer, and step back through them.
+        let mut history = vec![Deque::new()];
+        for c in "hello".chars() {
+            history.push(history.last().unwrap().push_back(c));
+        }
+        let edited = history
+            .last()
+            .unwrap()
+            .pop_front()
+            .unwrap()
+            .1
+            .push_front('j');
+        history.push(edited);
+
+        let text = |deque: &Deque<char>| deque.iter().collect::<String>();
+        assert_eq!(text(&history[6]), "jello");
+        assert_eq!(text(&history[5]), "hello");
+        assert_eq!(text(&history[2]), "he");
+        assert_eq!(text(&history[0]), "");
+    }
+
+    #[test]
+    fn long_queue() {
+        // Dropping long streams must not overflow the stack.
+        let mut queue: BankersQueue<_> = (0..100_000).collect();
+        for i in 0..50_000 {
+            let (elem, rest) = queue.pop_front().unwrap();
+            assert_eq!(*elem, i);
+            queue = rest;
+        }
+        assert_eq!(queue.len(), 50_000);
+        drop(queue);
+
+        let queue: Queue<_> = (0..100_000).collect();
+        drop(queue);
+
+        let deque: Deque<_> = (0..100_000).collect();
+        drop(deque);
+    }
+}
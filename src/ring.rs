@@ -0,0 +1,809 @@
+//! A circular doubly linked list, where the tail links back to the head.
+//!
+//! There is no ghost element: a [`CursorMut`] wraps around from the tail to
+//! the head and back, so it can spin around the ring forever, and rotating
+//! the ring only moves the head pointer.
+
+use core::{fmt::Debug, hash::Hash, iter::Cycle, marker::PhantomData, ptr::NonNull};
+
+use crate::util::drop_all;
+
+pub struct Ring<T> {
+    head: Option<NonNull<Node<T>>>,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+struct Node<T> {
+    // In a ring of one, both link to the node itself.
+    prev: NonNull<Node<T>>,
+    next: NonNull<Node<T>>,
+    elem: T,
+}
+
+impl<T> Ring<T> {
+    /// Creates a new [`Ring`].
+    pub fn new() -> Self {
+        Self {
+            head: None,
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Inserts an element at the front of the ring, which becomes its head.
+    pub fn push_front(&mut self, elem: T) {
+        self.head = Some(self.link_before(self.head, elem));
+    }
+
+    /// Inserts an element at the back of the ring, just before its head.
+    pub fn push_back(&mut self, elem: T) {
+        self.link_before(self.head, elem);
+    }
+
+    /// Removes the element at the head, and returns it.
+    pub fn pop_front(&mut self) -> Option<T> {
+        let head = self.head?;
+        unsafe { Some(self.unlink(head)) }
+    }
+
+    /// Removes the element just before the head, and returns it.
+    pub fn pop_back(&mut self) -> Option<T> {
+        let tail = self.tail()?;
+        unsafe { Some(self.unlink(tail)) }
+    }
+
+    /// Returns a reference to the element at the head.
+    pub fn front(&self) -> Option<&T> {
+        unsafe { Some(&(*self.head?.as_ptr()).elem) }
+    }
+
+    /// Returns a mutable reference to the element at the head.
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        unsafe { Some(&mut (*self.head?.as_ptr()).elem) }
+    }
+
+    /// Returns a reference to the element just before the head.
+    pub fn back(&self) -> Option<&T> {
+        unsafe { Some(&(*self.tail()?.as_ptr()).elem) }
+    }
+
+    /// Returns a mutable reference to the element just before the head.
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        unsafe { Some(&mut (*self.tail()?.as_ptr()).elem) }
+    }
+
+    /// The length of the ring.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the ring is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clears the collection.
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    /// Rotates the ring `n` places to the left, so the element `n` places
+    /// after the head becomes the head.
+    ///
+    /// `n` wraps around the ring, and the head moves O(min(n, len - n)) places
+    /// in whichever direction is shorter.
+    pub fn rotate_left(&mut self, n: usize) {
+        if let Some(head) = self.head {
+            self.head = Some(unsafe { self.walk(head, (n % self.len) as isize) });
+        }
+    }
+
+    /// Rotates the ring `n` places to the right, so the element `n` places
+    /// before the head becomes the head.
+    ///
+    /// `n` wraps around the ring, and the head moves O(min(n, len - n)) places
+    /// in whichever direction is shorter.
+    pub fn rotate_right(&mut self, n: usize) {
+        if let Some(head) = self.head {
+            self.head = Some(unsafe { self.walk(head, -((n % self.len) as isize)) });
+        }
+    }
+
+    fn tail(&self) -> Option<NonNull<Node<T>>> {
+        self.head.map(|head| unsafe { (*head.as_ptr()).prev })
+    }
+
+    /// Returns the node `offset` places after `node`, wrapping around and
+    /// walking in whichever direction is shorter.
+    ///
+    /// # Safety
+    ///
+    /// `node` must belong to this ring.
+    unsafe fn walk(&self, mut node: NonNull<Node<T>>, offset: isize) -> NonNull<Node<T>> {
+        let forward = offset.rem_euclid(self.len as isize) as usize;
+        if forward <= self.len / 2 {
+            for _ in 0..forward {
+                node = (*node.as_ptr()).next;
+            }
+        } else {
+            for _ in forward..self.len {
+                node = (*node.as_ptr()).prev;
+            }
+        }
+        node
+    }
+
+    /// Allocates a node holding `elem` and links it just before `at`, or as
+    /// the only node if the ring is empty, in which case it becomes the head.
+    fn link_before(&mut self, at: Option<NonNull<Node<T>>>, elem: T) -> NonNull<Node<T>> {
+        unsafe {
+            let new_node = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                prev: NonNull::dangling(),
+                next: NonNull::dangling(),
+                elem,
+            })));
+
+            let (prev, next) = match at {
+                Some(at) => ((*at.as_ptr()).prev, at),
+                None => {
+                    self.head = Some(new_node);
+                    (new_node, new_node)
+                }
+            };
+            (*new_node.as_ptr()).prev = prev;
+            (*new_node.as_ptr()).next = next;
+            (*prev.as_ptr()).next = new_node;
+            (*next.as_ptr()).prev = new_node;
+
+            self.len += 1;
+            new_node
+        }
+    }
+
+    /// Unlinks and frees `node`, and returns its element. If it was the head,
+    /// the next node becomes the head.
+    ///
+    /// # Safety
+    ///
+    /// `node` must belong to this ring.
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) -> T {
+        let boxed_node = Box::from_raw(node.as_ptr());
+        if boxed_node.next == node {
+            // The ring became empty.
+            self.head = None;
+        } else {
+            (*boxed_node.prev.as_ptr()).next = boxed_node.next;
+            (*boxed_node.next.as_ptr()).prev = boxed_node.prev;
+            if self.head == Some(node) {
+                self.head = Some(boxed_node.next);
+            }
+        }
+        self.len -= 1;
+        boxed_node.elem
+    }
+}
+
+impl<T> Default for Ring<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for Ring<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T> Extend<T> for Ring<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for Ring<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut ring = Self::new();
+        ring.extend(iter);
+        ring
+    }
+}
+
+impl<T: Debug> Debug for Ring<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for Ring<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for Ring<T> {}
+
+impl<T: PartialOrd> PartialOrd for Ring<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for Ring<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for Ring<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<T> Drop for Ring<T> {
+    fn drop(&mut self) {
+        drop_all(self, Self::pop_front);
+    }
+}
+
+// The ring owns its elements, so it is as thread-safe as they are.
+unsafe impl<T: Send> Send for Ring<T> {}
+unsafe impl<T: Sync> Sync for Ring<T> {}
+
+/// An iterator going once around the ring, from any starting element.
+pub struct Iter<'a, T> {
+    front: Option<NonNull<Node<T>>>,
+    back: Option<NonNull<Node<T>>>,
+    len: usize,
+    _marker: PhantomData<&'a T>,
+}
+
+impl<T> Ring<T> {
+    /// Returns an iterator once around the ring, starting at the head.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.iter_from(0)
+    }
+
+    /// Returns an iterator once around the ring, starting `start` places after
+    /// the head and wrapping around to end just before it.
+    pub fn iter_from(&self, start: usize) -> Iter<'_, T> {
+        let front = self
+            .head
+            .map(|head| unsafe { self.walk(head, (start % self.len) as isize) });
+        Iter {
+            front,
+            back: front.map(|front| unsafe { (*front.as_ptr()).prev }),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns an iterator spinning around the ring forever, starting `start`
+    /// places after the head. It is empty if the ring is.
+    pub fn round_robin(&self, start: usize) -> Cycle<Iter<'_, T>> {
+        self.iter_from(start).cycle()
+    }
+}
+
+impl<'a, T> Clone for Iter<'a, T> {
+    fn clone(&self) -> Self {
+        Self { ..*self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Ring<T> {
+    type Item = &'a T;
+
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|front| unsafe {
+                self.len -= 1;
+                self.front = Some((*front.as_ptr()).next);
+                &(*front.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|back| unsafe {
+                self.len -= 1;
+                self.back = Some((*back.as_ptr()).prev);
+                &(*back.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+/// A mutable iterator going once around the ring, from any starting element.
+pub struct IterMut<'a, T> {
+    front: Option<NonNull<Node<T>>>,
+    back: Option<NonNull<Node<T>>>,
+    len: usize,
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<T> Ring<T> {
+    /// Returns a mutable iterator once around the ring, starting at the head.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        self.iter_mut_from(0)
+    }
+
+    /// Returns a mutable iterator once around the ring, starting `start` places
+    /// after the head and wrapping around to end just before it.
+    pub fn iter_mut_from(&mut self, start: usize) -> IterMut<'_, T> {
+        let Iter {
+            front, back, len, ..
+        } = self.iter_from(start);
+        IterMut {
+            front,
+            back,
+            len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Ring<T> {
+    type Item = &'a mut T;
+
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|front| unsafe {
+                self.len -= 1;
+                self.front = Some((*front.as_ptr()).next);
+                &mut (*front.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|back| unsafe {
+                self.len -= 1;
+                self.back = Some((*back.as_ptr()).prev);
+                &mut (*back.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+pub struct IntoIter<T> {
+    ring: Ring<T>,
+}
+
+impl<T> IntoIterator for Ring<T> {
+    type Item = T;
+
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { ring: self }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.ring.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.ring.len, Some(self.ring.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.ring.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+/// A cursor over a [`Ring`], which wraps around from the back to the front.
+///
+/// The cursor is always at an element, unless the ring is empty.
+pub struct CursorMut<'a, T> {
+    cur: Option<NonNull<Node<T>>>,
+    ring: &'a mut Ring<T>,
+    // Places after the head, or 0 if the ring is empty.
+    index: usize,
+}
+
+impl<T> Ring<T> {
+    /// Returns a cursor at the head.
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            cur: self.head,
+            ring: self,
+            index: 0,
+        }
+    }
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    /// Retreive the index of the cursor, counted from the head, or `None` if
+    /// the ring is empty.
+    pub fn index(&self) -> Option<usize> {
+        self.cur.map(|_| self.index)
+    }
+
+    /// Move the cursor to the next element, wrapping around to the head.
+    pub fn move_next(&mut self) {
+        if let Some(cur) = self.cur {
+            self.cur = Some(unsafe { (*cur.as_ptr()).next });
+            self.index = (self.index + 1) % self.ring.len;
+        }
+    }
+
+    /// Move the cursor to the previous element, wrapping around to the back.
+    pub fn move_prev(&mut self) {
+        if let Some(cur) = self.cur {
+            self.cur = Some(unsafe { (*cur.as_ptr()).prev });
+            self.index = (self.index + self.ring.len - 1) % self.ring.len;
+        }
+    }
+
+    /// Retrieve the element at the cursor.
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.cur.map(|cur| unsafe { &mut (*cur.as_ptr()).elem })
+    }
+
+    /// Retrieve the element after the cursor, which is the current one if it
+    /// is alone in the ring.
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        self.cur
+            .map(|cur| unsafe { &mut (*(*cur.as_ptr()).next.as_ptr()).elem })
+    }
+
+    /// Retrieve the element before the cursor, which is the current one if it
+    /// is alone in the ring.
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        self.cur
+            .map(|cur| unsafe { &mut (*(*cur.as_ptr()).prev.as_ptr()).elem })
+    }
+
+    /// Inserts an element after the cursor. In an empty ring, the cursor
+    /// moves to the new element.
+    pub fn insert_after(&mut self, elem: T) {
+        match self.cur {
+            Some(cur) => {
+                let next = unsafe { (*cur.as_ptr()).next };
+                self.ring.link_before(Some(next), elem);
+            }
+            None => self.cur = Some(self.ring.link_before(None, elem)),
+        }
+    }
+
+    /// Inserts an element before the cursor. In an empty ring, the cursor
+    /// moves to the new element.
+    ///
+    /// If the cursor is at the head, the element goes to the back of the ring.
+    pub fn insert_before(&mut self, elem: T) {
+        match self.cur {
+            Some(cur) => {
+                self.ring.link_before(Some(cur), elem);
+                if self.ring.head != Some(cur) {
+                    self.index += 1;
+                }
+            }
+            None => self.cur = Some(self.ring.link_before(None, elem)),
+        }
+    }
+
+    /// Removes the current element and returns it.
+    /// The cursor will be moved to the next element, wrapping around.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let cur = self.cur?;
+        unsafe {
+            let next = (*cur.as_ptr()).next;
+            let elem = self.ring.unlink(cur);
+            if self.ring.is_empty() {
+                self.cur = None;
+            } else {
+                self.cur = Some(next);
+                if self.ring.head == Some(next) {
+                    // Either the back was removed and we wrapped around, or
+                    // the head was removed and its next took over.
+                    self.index = 0;
+                }
+            }
+            Some(elem)
+        }
+    }
+
+    /// Rotates the ring so that the current element becomes the head.
+    pub fn make_head(&mut self) {
+        self.ring.head = self.cur;
+        self.index = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Ring;
+    use crate::util::test::{assert_send_sync, panics, Drops};
+
+    /// Checks that the ring is consistently linked in both directions.
+    fn check_links<T: Eq + std::fmt::Debug>(ring: &Ring<T>) {
+        let from_front: Vec<_> = ring.iter().collect();
+        let from_back: Vec<_> = ring.iter().rev().collect();
+        let re_reved: Vec<_> = from_back.into_iter().rev().collect();
+        assert_eq!(from_front, re_reved);
+        assert_eq!(from_front.len(), ring.len());
+
+        if let Some(head) = ring.head {
+            unsafe {
+                let mut node = head;
+                for _ in 0..ring.len() {
+                    assert_eq!((*(*node.as_ptr()).next.as_ptr()).prev, node);
+                    node = (*node.as_ptr()).next;
+                }
+                assert_eq!(node, head);
+            }
+        }
+    }
+
+    fn elems(ring: &Ring<i32>) -> Vec<i32> {
+        ring.iter().copied().collect()
+    }
+
+    #[test]
+    fn basics() {
+        let mut ring = Ring::new();
+        assert_eq!(ring.pop_front(), None);
+        assert_eq!(ring.pop_back(), None);
+        assert_eq!(ring.front(), None);
+
+        ring.push_back(2);
+        ring.push_front(1);
+        ring.push_back(3);
+        check_links(&ring);
+        assert_eq!(elems(&ring), [1, 2, 3]);
+        assert_eq!((ring.front(), ring.back()), (Some(&1), Some(&3)));
+        *ring.front_mut().unwrap() = 10;
+        *ring.back_mut().unwrap() = 30;
+
+        assert_eq!(ring.pop_back(), Some(30));
+        assert_eq!(ring.pop_front(), Some(10));
+        check_links(&ring);
+        assert_eq!(ring.pop_front(), Some(2));
+        assert!(ring.is_empty());
+        assert_eq!(ring.pop_back(), None);
+    }
+
+    #[test]
+    fn rotate() {
+        let mut ring: Ring<i32> = (0..5).collect();
+        ring.rotate_left(2);
+        check_links(&ring);
+        assert_eq!(elems(&ring), [2, 3, 4, 0, 1]);
+        ring.rotate_right(3);
+        assert_eq!(elems(&ring), [4, 0, 1, 2, 3]);
+        // Rotations wrap around.
+        ring.rotate_left(11);
+        assert_eq!(elems(&ring), [0, 1, 2, 3, 4]);
+        ring.rotate_right(5);
+        assert_eq!(elems(&ring), [0, 1, 2, 3, 4]);
+
+        for n in 0..12 {
+            let mut left: Ring<i32> = (0..5).collect();
+            let mut right: Ring<i32> = (0..5).collect();
+            left.rotate_left(n);
+            right.rotate_right(5 - n % 5);
+            assert_eq!(left, right);
+        }
+
+        let mut empty: Ring<i32> = Ring::new();
+        empty.rotate_left(3);
+        empty.rotate_right(3);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn iterators() {
+        let mut ring: Ring<i32> = (0..5).collect();
+        assert!(ring.iter_from(3).eq(&[3, 4, 0, 1, 2]));
+        assert!(ring.iter_from(8).rev().eq(&[2, 1, 0, 4, 3]));
+        assert_eq!(ring.iter_from(1).len(), 5);
+        assert!(ring.round_robin(4).take(7).eq(&[4, 0, 1, 2, 3, 4, 0]));
+
+        for elem in ring.iter_mut_from(2).take(2) {
+            *elem *= 10;
+        }
+        assert_eq!(elems(&ring), [0, 1, 20, 30, 4]);
+        assert_eq!(
+            ring.into_iter().rev().collect::<Vec<_>>(),
+            [4, 30, 20, 1, 0]
+        );
+
+        let empty: Ring<i32> = Ring::new();
+        assert_eq!(empty.iter_from(3).next(), None);
+        assert_eq!(empty.round_robin(0).next(), None);
+    }
+
+    #[test]
+    fn cursor_wraps() {
+        let mut ring: Ring<i32> = (0..3).collect();
+        let mut cursor = ring.cursor_mut();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.peek_prev(), Some(&mut 2));
+
+        let seen: Vec<_> = (0..7)
+            .map(|_| {
+                cursor.move_next();
+                (*cursor.current().unwrap(), cursor.index().unwrap())
+            })
+            .collect();
+        assert_eq!(
+            seen,
+            [(1, 1), (2, 2), (0, 0), (1, 1), (2, 2), (0, 0), (1, 1)]
+        );
+
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 2));
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.peek_next(), Some(&mut 0));
+
+        let mut single: Ring<i32> = Some(7).into_iter().collect();
+        let mut cursor = single.cursor_mut();
+        cursor.move_next();
+        assert_eq!(cursor.peek_next(), Some(&mut 7));
+        assert_eq!(cursor.index(), Some(0));
+    }
+
+    #[test]
+    fn cursor_insert_remove() {
+        let mut ring: Ring<i32> = Ring::new();
+        let mut cursor = ring.cursor_mut();
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.remove_current(), None);
+        cursor.insert_after(1);
+        assert_eq!(cursor.current(), Some(&mut 1));
+        cursor.insert_after(3);
+        cursor.insert_after(2);
+        cursor.insert_before(0);
+        assert_eq!(cursor.index(), Some(0));
+        check_links(&ring);
+        assert_eq!(elems(&ring), [1, 2, 3, 0]);
+
+        let mut cursor = ring.cursor_mut();
+        cursor.move_next();
+        cursor.insert_before(10);
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        check_links(&ring);
+        assert_eq!(elems(&ring), [1, 10, 2, 3, 0]);
+
+        // Removing the back wraps around to the head.
+        let mut cursor = ring.cursor_mut();
+        cursor.move_prev();
+        assert_eq!(cursor.index(), Some(4));
+        assert_eq!(cursor.remove_current(), Some(0));
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 1));
+
+        // Removing the head hands it to the next element.
+        assert_eq!(cursor.remove_current(), Some(1));
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(2));
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(cursor.current(), Some(&mut 3));
+        cursor.make_head();
+        assert_eq!(cursor.index(), Some(0));
+        check_links(&ring);
+        assert_eq!(elems(&ring), [3, 10]);
+
+        let mut cursor = ring.cursor_mut();
+        assert_eq!(cursor.remove_current(), Some(3));
+        assert_eq!(cursor.remove_current(), Some(10));
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.current(), None);
+        assert!(ring.is_empty());
+    }
+
+    #[test]
+    fn traits() {
+        let ring: Ring<i32> = (0..3).collect();
+        let mut other = ring.clone();
+        check_links(&other);
+        assert_eq!(ring, other);
+        other.rotate_left(1);
+        assert!(ring != other);
+        assert!(ring < other);
+        assert_eq!(format!("{:?}", ring), "[0, 1, 2]");
+
+        let mut map = std::collections::HashMap::new();
+        map.insert(ring.clone(), "ring");
+        assert_eq!(map.get(&ring), Some(&"ring"));
+    }
+
+    #[test]
+    fn send_sync() {
+        assert_send_sync::<Ring<i32>>();
+        assert_send_sync::<super::Iter<'_, i32>>();
+        assert_send_sync::<super::IterMut<'_, i32>>();
+    }
+
+    #[test]
+    fn drop_panic() {
+        let drops = Drops::default();
+        let mut ring = Ring::new();
+        ring.push_back(drops.elem(false));
+        ring.push_back(drops.elem(true));
+        ring.push_back(drops.elem(false));
+        ring.push_front(drops.elem(false));
+
+        assert!(panics(move || drop(ring)));
+        assert_eq!(drops.count(), 4);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::viz::{Dot, Viz};
+
 #[derive(Debug)]
 struct Node<T> {
     elem: T,
@@ -45,6 +47,18 @@ impl<T> List<T> {
         self.head.as_mut().map(|node| &mut node.elem)
     }
 
+    /// Reverses the list in place, by relinking its nodes.
+    pub fn reverse(&mut self) {
+        let mut reversed = None;
+        let mut cur_link = self.head.take();
+        while let Some(mut boxed_node) = cur_link {
+            cur_link = boxed_node.next.take();
+            boxed_node.next = reversed;
+            reversed = Some(boxed_node);
+        }
+        self.head = reversed;
+    }
+
     /// Returns an iterator over the elements of the list.
     pub fn into_iter(self) -> IntoIter<T> {
         IntoIter(self)
@@ -74,6 +88,28 @@ impl<T> Drop for List<T> {
     }
 }
 
+impl<T: core::fmt::Debug> Viz for List<T> {
+    fn viz(&self, name: &str, dot: &mut Dot) {
+        dot.entry(name, name);
+        dot.pointer(
+            name,
+            "head",
+            self.head.as_deref().map(|node| node as *const _),
+        );
+
+        let mut cur_link = self.head.as_deref();
+        while let Some(node) = cur_link {
+            dot.node(node, &node.elem, None);
+            dot.edge(
+                node,
+                node.next.as_deref().map(|next| next as *const _),
+                "next",
+            );
+            cur_link = node.next.as_deref();
+        }
+    }
+}
+
 pub struct IntoIter<T>(List<T>);
 
 impl<T> Iterator for IntoIter<T> {
@@ -212,4 +248,23 @@ mod test {
         assert_eq!(iter.next(), Some(&2));
         assert_eq!(iter.next(), Some(&1));
     }
+
+    #[test]
+    fn reverse() {
+        let mut list = List::new();
+        list.reverse();
+        assert_eq!(list.peek(), None);
+
+        list.push(1);
+        list.push(2);
+        list.push(3);
+        list.reverse();
+        assert_eq!(list.iter().collect::<Vec<_>>(), [&1, &2, &3]);
+
+        list.push(0);
+        assert_eq!(list.pop(), Some(0));
+        assert_eq!(list.pop(), Some(1));
+        list.reverse();
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), [3, 2]);
+    }
 }
@@ -0,0 +1,997 @@
+//! An ordered map and set on a skip list: a sorted linked list whose nodes
+//! also link forward at randomly chosen higher levels, each skipping over
+//! about twice as many nodes as the one below. Searching descends from the
+//! highest level, which takes O(log n) expected steps instead of a linear scan.
+
+use core::{
+    borrow::Borrow,
+    fmt::{self, Debug},
+    hash::{BuildHasher, Hash, Hasher},
+    iter,
+    marker::PhantomData,
+    mem::{self, ManuallyDrop},
+    ops::{Bound, RangeBounds},
+    ptr::NonNull,
+};
+use std::collections::hash_map::RandomState;
+
+use crate::util::drop_all;
+
+/// The maximum number of levels, plenty for any number of elements that fits
+/// in memory.
+const MAX_LEVEL: usize = 32;
+
+type Link<K, V> = Option<NonNull<Node<K, V>>>;
+
+/// The predecessor of a position at every level, where `None` is the head.
+type Update<K, V> = [Link<K, V>; MAX_LEVEL];
+
+pub struct SkipMap<K, V> {
+    // Forward links of the head, one per level.
+    head: [Link<K, V>; MAX_LEVEL],
+    tail: Link<K, V>,
+    // The number of levels in use.
+    level: usize,
+    len: usize,
+    // State of the xorshift generator for the levels of new nodes.
+    rng: u64,
+    _marker: PhantomData<Box<Node<K, V>>>,
+}
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    // Only linked at level 0, for iterating backwards.
+    prev: Link<K, V>,
+    // Forward links, one per level of the node.
+    next: Box<[Link<K, V>]>,
+}
+
+impl<K, V> SkipMap<K, V> {
+    /// Creates an empty [`SkipMap`], with randomly seeded levels.
+    pub fn new() -> Self {
+        Self::with_seed(RandomState::new().build_hasher().finish())
+    }
+
+    /// Creates an empty [`SkipMap`], whose levels are chosen deterministically
+    /// from `seed`, so the same inserts always build the same structure.
+    pub fn with_seed(seed: u64) -> Self {
+        // Spread the seed with splitmix64, as xorshift is stuck at zero.
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        Self {
+            head: [None; MAX_LEVEL],
+            tail: None,
+            level: 0,
+            len: 0,
+            rng: (z ^ (z >> 31)).max(1),
+            _marker: PhantomData,
+        }
+    }
+
+    /// The number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clears the map.
+    pub fn clear(&mut self) {
+        drop(mem::replace(self, Self::with_seed(self.rng)));
+    }
+
+    /// Returns the entry with the smallest key.
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.head[0].map(|node| unsafe { entry(node) })
+    }
+
+    /// Returns the entry with the largest key.
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.tail.map(|node| unsafe { entry(node) })
+    }
+
+    /// Removes the entry with the smallest key, and returns it.
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        let node = self.head[0]?;
+        // The first node directly follows the head at all of its levels.
+        let node = unsafe { self.unlink(&[None; MAX_LEVEL], node) };
+        Some((node.key, node.value))
+    }
+
+    /// Returns an iterator over the entries, in order of their keys.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            range: Range {
+                front: self.head[0],
+                back: self.tail,
+                _marker: PhantomData,
+            },
+            len: self.len,
+        }
+    }
+
+    /// Returns the node following `pred` at `level`.
+    unsafe fn next_at(&self, pred: Link<K, V>, level: usize) -> Link<K, V> {
+        match pred {
+            Some(pred) => (*pred.as_ptr()).next[level],
+            None => self.head[level],
+        }
+    }
+
+    unsafe fn set_next(&mut self, pred: Link<K, V>, level: usize, link: Link<K, V>) {
+        match pred {
+            Some(pred) => (*pred.as_ptr()).next[level] = link,
+            None => self.head[level] = link,
+        }
+    }
+
+    /// Returns the last node at every level whose key satisfies `goes_before`,
+    /// which must hold for a prefix of the keys.
+    unsafe fn search(&self, mut goes_before: impl FnMut(&K) -> bool) -> Update<K, V> {
+        let mut update = [None; MAX_LEVEL];
+        let mut pred = None;
+        for level in (0..self.level).rev() {
+            while let Some(next) = self.next_at(pred, level) {
+                if !goes_before(&(*next.as_ptr()).key) {
+                    break;
+                }
+                pred = Some(next);
+            }
+            update[level] = pred;
+        }
+        update
+    }
+
+    /// Unlinks `node` from its predecessors in `update`, and takes it back.
+    unsafe fn unlink(
+        &mut self,
+        update: &Update<K, V>,
+        node: NonNull<Node<K, V>>,
+    ) -> Box<Node<K, V>> {
+        let boxed_node = Box::from_raw(node.as_ptr());
+        for (level, &next) in boxed_node.next.iter().enumerate() {
+            self.set_next(update[level], level, next);
+        }
+        match boxed_node.next[0] {
+            Some(next) => (*next.as_ptr()).prev = boxed_node.prev,
+            None => self.tail = boxed_node.prev,
+        }
+        while self.level > 0 && self.head[self.level - 1].is_none() {
+            self.level -= 1;
+        }
+        self.len -= 1;
+        boxed_node
+    }
+
+    /// Picks the level of a new node, where each level is half as likely as
+    /// the one below.
+    fn random_level(&mut self) -> usize {
+        let mut x = self.rng;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.rng = x;
+        (x.trailing_ones() as usize + 1).min(MAX_LEVEL)
+    }
+}
+
+impl<K: Ord, V> SkipMap<K, V> {
+    /// Inserts a value for `key`, and returns the value it replaced.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        unsafe {
+            let update = self.search(|k| *k < key);
+            if let Some(node) = self.next_at(update[0], 0) {
+                if (*node.as_ptr()).key == key {
+                    return Some(mem::replace(&mut (*node.as_ptr()).value, value));
+                }
+            }
+
+            // Levels above the ones in use start at the head, which is what
+            // `update` already holds for them.
+            let height = self.random_level();
+            self.level = self.level.max(height);
+
+            let mut next = vec![None; height].into_boxed_slice();
+            for (level, next) in next.iter_mut().enumerate() {
+                *next = self.next_at(update[level], level);
+            }
+            let node = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                key,
+                value,
+                prev: update[0],
+                next,
+            })));
+
+            for (level, &pred) in update.iter().enumerate().take(height) {
+                self.set_next(pred, level, Some(node));
+            }
+            match (*node.as_ptr()).next[0] {
+                Some(next) => (*next.as_ptr()).prev = Some(node),
+                None => self.tail = Some(node),
+            }
+            self.len += 1;
+        }
+        None
+    }
+
+    /// Returns a reference to the value of `key`.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let node = self.find(key)?;
+        unsafe { Some(&(*node.as_ptr()).value) }
+    }
+
+    /// Returns a mutable reference to the value of `key`.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let node = self.find(key)?;
+        unsafe { Some(&mut (*node.as_ptr()).value) }
+    }
+
+    /// Returns `true` if the map holds `key`.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    /// Removes `key` from the map, and returns its value.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Removes `key` from the map, and returns the stored key and its value.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        unsafe {
+            let update = self.search(|k| k.borrow() < key);
+            let node = self.next_at(update[0], 0)?;
+            if (*node.as_ptr()).key.borrow() != key {
+                return None;
+            }
+            let node = self.unlink(&update, node);
+            Some((node.key, node.value))
+        }
+    }
+
+    /// Removes the entry with the largest key, and returns it.
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        let node = self.tail?;
+        unsafe {
+            let update = self.search(|k| *k < (*node.as_ptr()).key);
+            let node = self.unlink(&update, node);
+            Some((node.key, node.value))
+        }
+    }
+
+    /// Returns a double-ended iterator over the entries with keys in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if they
+    /// are equal and both excluded, like [`BTreeMap::range`](std::collections::BTreeMap::range).
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        match (range.start_bound(), range.end_bound()) {
+            (Bound::Excluded(start), Bound::Excluded(end)) if start == end => {
+                panic!("range start and end are equal and excluded in SkipMap")
+            }
+            (
+                Bound::Included(start) | Bound::Excluded(start),
+                Bound::Included(end) | Bound::Excluded(end),
+            ) if start > end => panic!("range start is greater than range end in SkipMap"),
+            _ => {}
+        }
+
+        unsafe {
+            let front = match range.start_bound() {
+                Bound::Included(start) => self.next_at(self.search(|k| k.borrow() < start)[0], 0),
+                Bound::Excluded(start) => self.next_at(self.search(|k| k.borrow() <= start)[0], 0),
+                Bound::Unbounded => self.head[0],
+            };
+            let back = match range.end_bound() {
+                Bound::Included(end) => self.search(|k| k.borrow() <= end)[0],
+                Bound::Excluded(end) => self.search(|k| k.borrow() < end)[0],
+                Bound::Unbounded => self.tail,
+            };
+
+            match (front, back) {
+                (Some(front), Some(back)) if (*front.as_ptr()).key <= (*back.as_ptr()).key => {
+                    Range {
+                        front: Some(front),
+                        back: Some(back),
+                        _marker: PhantomData,
+                    }
+                }
+                // The bounds crossed over, so there is nothing in between.
+                _ => Range {
+                    front: None,
+                    back: None,
+                    _marker: PhantomData,
+                },
+            }
+        }
+    }
+
+    fn find<Q>(&self, key: &Q) -> Link<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        unsafe {
+            let node = self.next_at(self.search(|k| k.borrow() < key)[0], 0)?;
+            ((*node.as_ptr()).key.borrow() == key).then_some(node)
+        }
+    }
+}
+
+/// Borrows the entry of `node` for as long as the caller needs.
+unsafe fn entry<'a, K, V>(node: NonNull<Node<K, V>>) -> (&'a K, &'a V) {
+    let node = &*node.as_ptr();
+    (&node.key, &node.value)
+}
+
+impl<K, V> Default for SkipMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Clone for SkipMap<K, V> {
+    fn clone(&self) -> Self {
+        let mut map = Self::with_seed(self.rng);
+        map.extend(self.iter().map(|(key, value)| (key.clone(), value.clone())));
+        map
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for SkipMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SkipMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for SkipMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: PartialEq, V: PartialEq> PartialEq for SkipMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for SkipMap<K, V> {}
+
+impl<K: PartialOrd, V: PartialOrd> PartialOrd for SkipMap<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<K: Ord, V: Ord> Ord for SkipMap<K, V> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<K: Hash, V: Hash> Hash for SkipMap<K, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for entry in self {
+            entry.hash(state);
+        }
+    }
+}
+
+impl<K, V> Drop for SkipMap<K, V> {
+    fn drop(&mut self) {
+        // Dropping the nodes only needs the links at level 0.
+        drop(IntoIter {
+            front: self.head[0],
+            back: self.tail,
+            len: self.len,
+            _marker: PhantomData,
+        });
+    }
+}
+
+// The map owns its entries, so it is as thread-safe as they are.
+unsafe impl<K: Send, V: Send> Send for SkipMap<K, V> {}
+unsafe impl<K: Sync, V: Sync> Sync for SkipMap<K, V> {}
+
+/// A double-ended iterator over a range of entries of a [`SkipMap`].
+pub struct Range<'a, K, V> {
+    // Both are `None` once the range is exhausted.
+    front: Link<K, V>,
+    back: Link<K, V>,
+    _marker: PhantomData<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.front?;
+        unsafe {
+            if self.front == self.back {
+                self.front = None;
+                self.back = None;
+            } else {
+                self.front = (*node.as_ptr()).next[0];
+            }
+            Some(entry(node))
+        }
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Range<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.back?;
+        unsafe {
+            if self.front == self.back {
+                self.front = None;
+                self.back = None;
+            } else {
+                self.back = (*node.as_ptr()).prev;
+            }
+            Some(entry(node))
+        }
+    }
+}
+
+unsafe impl<'a, K: Sync, V: Sync> Send for Range<'a, K, V> {}
+unsafe impl<'a, K: Sync, V: Sync> Sync for Range<'a, K, V> {}
+
+/// An iterator over all the entries of a [`SkipMap`].
+pub struct Iter<'a, K, V> {
+    range: Range<'a, K, V>,
+    len: usize,
+}
+
+impl<'a, K, V> IntoIterator for &'a SkipMap<K, V> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.range.next()?;
+        self.len -= 1;
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let entry = self.range.next_back()?;
+        self.len -= 1;
+        Some(entry)
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+pub struct IntoIter<K, V> {
+    front: Link<K, V>,
+    back: Link<K, V>,
+    len: usize,
+    _marker: PhantomData<Box<Node<K, V>>>,
+}
+
+impl<K, V> IntoIterator for SkipMap<K, V> {
+    type Item = (K, V);
+
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        // The iterator takes over the nodes, following only their level 0 links.
+        let map = ManuallyDrop::new(self);
+        IntoIter {
+            front: map.head[0],
+            back: map.tail,
+            len: map.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|front| unsafe {
+                let boxed_node = Box::from_raw(front.as_ptr());
+                self.len -= 1;
+                self.front = boxed_node.next[0];
+                (boxed_node.key, boxed_node.value)
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|back| unsafe {
+                let boxed_node = Box::from_raw(back.as_ptr());
+                self.len -= 1;
+                self.back = boxed_node.prev;
+                (boxed_node.key, boxed_node.value)
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+
+impl<K, V> Drop for IntoIter<K, V> {
+    fn drop(&mut self) {
+        drop_all(self, Self::next);
+    }
+}
+
+unsafe impl<K: Send, V: Send> Send for IntoIter<K, V> {}
+unsafe impl<K: Sync, V: Sync> Sync for IntoIter<K, V> {}
+
+/// An ordered set, as a [`SkipMap`] without values.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SkipSet<T> {
+    map: SkipMap<T, ()>,
+}
+
+impl<T> SkipSet<T> {
+    /// Creates an empty [`SkipSet`], with randomly seeded levels.
+    pub fn new() -> Self {
+        Self {
+            map: SkipMap::new(),
+        }
+    }
+
+    /// Creates an empty [`SkipSet`], whose levels are chosen deterministically
+    /// from `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            map: SkipMap::with_seed(seed),
+        }
+    }
+
+    /// The number of elements in the set.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Clears the set.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Returns the smallest element.
+    pub fn first(&self) -> Option<&T> {
+        self.map.first_key_value().map(|(elem, _)| elem)
+    }
+
+    /// Returns the largest element.
+    pub fn last(&self) -> Option<&T> {
+        self.map.last_key_value().map(|(elem, _)| elem)
+    }
+
+    /// Removes the smallest element, and returns it.
+    pub fn pop_first(&mut self) -> Option<T> {
+        self.map.pop_first().map(|(elem, _)| elem)
+    }
+
+    /// Returns an iterator over the elements, in order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.map.iter().map(|(elem, _)| elem)
+    }
+}
+
+impl<T: Ord> SkipSet<T> {
+    /// Adds an element to the set, and returns `true` if it was not there yet.
+    pub fn insert(&mut self, elem: T) -> bool {
+        if self.map.contains_key(&elem) {
+            return false;
+        }
+        self.map.insert(elem, ());
+        true
+    }
+
+    /// Returns `true` if the set holds `elem`.
+    pub fn contains<Q>(&self, elem: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.contains_key(elem)
+    }
+
+    /// Removes `elem` from the set, and returns `true` if it was there.
+    pub fn remove<Q>(&mut self, elem: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.remove(elem).is_some()
+    }
+
+    /// Removes the largest element, and returns it.
+    pub fn pop_last(&mut self) -> Option<T> {
+        self.map.pop_last().map(|(elem, _)| elem)
+    }
+
+    /// Returns a double-ended iterator over the elements in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics like [`SkipMap::range`].
+    pub fn range<Q, R>(&self, range: R) -> impl DoubleEndedIterator<Item = &T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        self.map.range(range).map(|(elem, _)| elem)
+    }
+}
+
+impl<T> Default for SkipSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone> Clone for SkipSet<T> {
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map.clone(),
+        }
+    }
+}
+
+impl<T: Ord> Extend<T> for SkipSet<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.insert(elem);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SkipSet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T: Debug> Debug for SkipSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T> IntoIterator for SkipSet<T> {
+    type Item = T;
+
+    type IntoIter = iter::Map<IntoIter<T, ()>, fn((T, ())) -> T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.into_iter().map(|(elem, ())| elem)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::ops::Bound;
+
+    use super::{SkipMap, SkipSet, MAX_LEVEL};
+    use crate::util::test::{assert_send_sync, panics, Drops};
+
+    /// Checks that every level is sorted and only holds nodes that tall, that
+    /// the `prev` links match level 0, and that the length is right.
+    fn check_links<K: Ord + std::fmt::Debug, V>(map: &SkipMap<K, V>) {
+        unsafe {
+            for level in 0..MAX_LEVEL {
+                let mut cur = map.head[level];
+                assert_eq!(cur.is_some(), level < map.level);
+                let mut prev = None;
+                let mut count = 0;
+                while let Some(node) = cur {
+                    let node = &*node.as_ptr();
+                    assert!(node.next.len() > level);
+                    if let Some(prev) = prev {
+                        assert!(prev < &node.key);
+                    }
+                    if level == 0 {
+                        assert_eq!(node.prev.map(|prev| &(*prev.as_ptr()).key), prev);
+                    }
+                    prev = Some(&node.key);
+                    cur = node.next[level];
+                    count += 1;
+                }
+                if level == 0 {
+                    assert_eq!(count, map.len());
+                    assert_eq!(map.tail.map(|tail| &(*tail.as_ptr()).key), prev);
+                }
+            }
+        }
+    }
+
+    /// The height of every node, in order.
+    fn heights<K, V>(map: &SkipMap<K, V>) -> Vec<usize> {
+        let mut heights = Vec::new();
+        let mut cur = map.head[0];
+        while let Some(node) = cur {
+            unsafe {
+                heights.push((*node.as_ptr()).next.as_ref().len());
+                cur = (*node.as_ptr()).next[0];
+            }
+        }
+        heights
+    }
+
+    /// A small xorshift generator, for reproducible operations.
+    fn keys(seed: u64) -> impl Iterator<Item = u32> {
+        let mut x = seed;
+        std::iter::repeat_with(move || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            (x % 500) as u32
+        })
+    }
+
+    #[test]
+    fn basics() {
+        let mut map = SkipMap::with_seed(1);
+        assert!(map.is_empty());
+        assert_eq!(map.get(&1), None);
+        assert_eq!(map.remove(&1), None);
+
+        assert_eq!(map.insert(2, "two"), None);
+        assert_eq!(map.insert(1, "one"), None);
+        assert_eq!(map.insert(3, "three"), None);
+        check_links(&map);
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.insert(2, "TWO"), Some("two"));
+        assert_eq!(map.len(), 3);
+
+        assert_eq!(map.get(&2), Some(&"TWO"));
+        *map.get_mut(&3).unwrap() = "THREE";
+        assert!(map.contains_key(&3));
+        assert!(!map.contains_key(&4));
+        assert_eq!(map.first_key_value(), Some((&1, &"one")));
+        assert_eq!(map.last_key_value(), Some((&3, &"THREE")));
+
+        assert_eq!(map.remove_entry(&2), Some((2, "TWO")));
+        check_links(&map);
+        assert_eq!(map.pop_last(), Some((3, "THREE")));
+        assert_eq!(map.pop_first(), Some((1, "one")));
+        check_links(&map);
+        assert!(map.is_empty());
+        assert_eq!(map.pop_first(), None);
+        assert_eq!(map.pop_last(), None);
+    }
+
+    #[test]
+    fn against_btree() {
+        let mut map = SkipMap::with_seed(7);
+        let mut model = BTreeMap::new();
+        for (i, key) in keys(0x2545_f491_4f6c_dd1d).take(3000).enumerate() {
+            if i % 3 == 2 {
+                assert_eq!(map.remove(&key), model.remove(&key));
+            } else {
+                assert_eq!(map.insert(key, i), model.insert(key, i));
+            }
+            assert_eq!(map.len(), model.len());
+            if i % 100 == 0 {
+                check_links(&map);
+            }
+        }
+        check_links(&map);
+        assert!(map.iter().eq(model.iter()));
+        assert!(map.iter().rev().eq(model.iter().rev()));
+        for key in 0..500 {
+            assert_eq!(map.get(&key), model.get(&key));
+        }
+
+        let mut iter = map.into_iter();
+        let mut model = model.into_iter();
+        loop {
+            let (a, b) = (iter.next(), model.next());
+            assert_eq!(a, b);
+            assert_eq!(iter.next_back(), model.next_back());
+            if a.is_none() {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn range() {
+        let map: SkipMap<u32, ()> = (0..100).step_by(3).map(|key| (key, ())).collect();
+        let model: BTreeMap<u32, ()> = (0..100).step_by(3).map(|key| (key, ())).collect();
+        let bounds = |x| [Bound::Included(x), Bound::Excluded(x), Bound::Unbounded];
+
+        for start in 0..=101 {
+            for end in start..=101 {
+                for (lo, hi) in bounds(start).into_iter().zip(bounds(end)) {
+                    if lo == Bound::Excluded(end) && hi == Bound::Excluded(end) {
+                        continue;
+                    }
+                    let keys: Vec<_> = map.range((lo, hi)).map(|(key, _)| *key).collect();
+                    let expected: Vec<_> = model.range((lo, hi)).map(|(key, _)| *key).collect();
+                    assert_eq!(keys, expected, "{:?}", (lo, hi));
+
+                    let rev: Vec<_> = map.range((lo, hi)).rev().map(|(key, _)| *key).collect();
+                    assert!(rev.iter().rev().eq(&expected));
+                }
+            }
+        }
+
+        // The ends meet in the middle.
+        let mut range = map.range(10..=30);
+        assert_eq!(range.next(), Some((&12, &())));
+        assert_eq!(range.next_back(), Some((&30, &())));
+        assert_eq!(range.next_back(), Some((&27, &())));
+        assert_eq!(range.next(), Some((&15, &())));
+        assert_eq!(range.next(), Some((&18, &())));
+        assert_eq!(range.next_back(), Some((&24, &())));
+        assert_eq!(range.next(), Some((&21, &())));
+        assert_eq!(range.next(), None);
+        assert_eq!(range.next_back(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "range start is greater than range end")]
+    fn range_backwards() {
+        let map: SkipMap<u32, ()> = SkipMap::new();
+        map.range((Bound::Included(2), Bound::Included(1)));
+    }
+
+    #[test]
+    fn seeding() {
+        let build = |seed| {
+            let mut map = SkipMap::with_seed(seed);
+            map.extend((0..1000).map(|key| (key, ())));
+            map
+        };
+        assert_eq!(heights(&build(42)), heights(&build(42)));
+        assert_ne!(heights(&build(42)), heights(&build(43)));
+
+        // About half of the nodes reach each next level.
+        let map = build(42);
+        let heights = heights(&map);
+        let tall = heights.iter().filter(|&&height| height > 1).count();
+        assert!((400..600).contains(&tall), "{} nodes above level 0", tall);
+        assert!(map.level <= 20, "{} levels", map.level);
+    }
+
+    #[test]
+    fn traits() {
+        let map: SkipMap<i32, &str> = [(2, "b"), (1, "a")].into_iter().collect();
+        assert_eq!(format!("{:?}", map), r#"{1: "a", 2: "b"}"#);
+
+        let mut other = map.clone();
+        check_links(&other);
+        assert_eq!(map, other);
+        other.insert(3, "c");
+        assert!(map != other);
+        assert!(map < other);
+        other.clear();
+        assert!(other.is_empty());
+        assert!(other < map);
+
+        let mut hash_map = std::collections::HashMap::new();
+        hash_map.insert(map.clone(), "map");
+        assert_eq!(hash_map.get(&map), Some(&"map"));
+    }
+
+    #[test]
+    fn set() {
+        let mut set: SkipSet<i32> = [5, 1, 3].into_iter().collect();
+        assert!(set.insert(4));
+        assert!(!set.insert(4));
+        assert_eq!(set.len(), 4);
+        assert!(set.contains(&3));
+        assert!(set.remove(&3));
+        assert!(!set.remove(&3));
+        assert_eq!(format!("{:?}", set), "{1, 4, 5}");
+        assert!(set.range(2..).eq(&[4, 5]));
+        assert!(set.iter().rev().eq(&[5, 4, 1]));
+        assert_eq!((set.first(), set.last()), (Some(&1), Some(&5)));
+        assert_eq!(set, set.clone());
+
+        assert_eq!(set.pop_first(), Some(1));
+        assert_eq!(set.pop_last(), Some(5));
+        set.extend([9, 7]);
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), [4, 7, 9]);
+    }
+
+    #[test]
+    fn send_sync() {
+        assert_send_sync::<SkipMap<i32, String>>();
+        assert_send_sync::<super::Iter<'_, i32, String>>();
+        assert_send_sync::<super::Range<'_, i32, String>>();
+        assert_send_sync::<super::IntoIter<i32, String>>();
+    }
+
+    #[test]
+    fn drop_panic() {
+        let drops = Drops::default();
+        let mut map = SkipMap::with_seed(0);
+        for key in 0..6 {
+            map.insert(key, drops.elem(key == 2));
+        }
+
+        assert!(panics(move || drop(map)));
+        assert_eq!(drops.count(), 6);
+    }
+}
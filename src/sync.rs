@@ -0,0 +1,505 @@
+//! A blocking bounded channel, using a [`LinkedList`] behind a [`Mutex`] as
+//! its queue.
+
+use std::{
+    error::Error,
+    fmt, mem,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::linked_list::LinkedList;
+
+/// The state shared by the [`Sender`]s and [`Receiver`]s of a channel.
+pub struct Channel<T> {
+    state: Mutex<State<T>>,
+    capacity: usize,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+
+struct State<T> {
+    queue: LinkedList<T>,
+    senders: usize,
+    receivers: usize,
+}
+
+/// The sending half of a channel.
+pub struct Sender<T> {
+    channel: Arc<Channel<T>>,
+}
+
+/// The receiving half of a channel.
+pub struct Receiver<T> {
+    channel: Arc<Channel<T>>,
+}
+
+/// Error returned by [`Sender::send`] when every receiver was dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+/// Error returned by [`Receiver::recv`] when the channel is empty and every
+/// sender was dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError;
+
+/// Error returned by [`Sender::try_send`] and [`Sender::send_timeout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendTimeoutError<T> {
+    /// The channel stayed full.
+    Timeout(T),
+    /// Every receiver was dropped.
+    Disconnected(T),
+}
+
+/// Error returned by [`Receiver::try_recv`] and [`Receiver::recv_timeout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvTimeoutError {
+    /// The channel stayed empty.
+    Timeout,
+    /// The channel is empty and every sender was dropped.
+    Disconnected,
+}
+
+impl<T> Channel<T> {
+    /// Creates a channel holding at most `capacity` messages, returning its
+    /// two halves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn bounded(capacity: usize) -> (Sender<T>, Receiver<T>) {
+        assert!(capacity > 0, "capacity must be positive");
+
+        let channel = Arc::new(Channel {
+            state: Mutex::new(State {
+                queue: LinkedList::new(),
+                senders: 1,
+                receivers: 1,
+            }),
+            capacity,
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+        });
+
+        (
+            Sender {
+                channel: channel.clone(),
+            },
+            Receiver { channel },
+        )
+    }
+
+    /// The maximum number of messages the channel holds.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of messages currently in the channel.
+    pub fn len(&self) -> usize {
+        self.lock().queue.len()
+    }
+
+    /// Returns `true` if the channel currently holds no message.
+    pub fn is_empty(&self) -> bool {
+        self.lock().queue.is_empty()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap()
+    }
+
+    /// Blocks until the channel has room or is disconnected, or until
+    /// `deadline` if there is one.
+    fn wait_not_full(&self, deadline: Option<Instant>) -> MutexGuard<'_, State<T>> {
+        let full = |state: &mut State<T>| state.queue.len() >= self.capacity && state.receivers > 0;
+        let state = self.lock();
+        match deadline {
+            None => self.not_full.wait_while(state, full).unwrap(),
+            Some(deadline) => {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                self.not_full
+                    .wait_timeout_while(state, timeout, full)
+                    .unwrap()
+                    .0
+            }
+        }
+    }
+
+    /// Blocks until the channel has a message or is disconnected, or until
+    /// `deadline` if there is one.
+    fn wait_not_empty(&self, deadline: Option<Instant>) -> MutexGuard<'_, State<T>> {
+        let empty = |state: &mut State<T>| state.queue.is_empty() && state.senders > 0;
+        let state = self.lock();
+        match deadline {
+            None => self.not_empty.wait_while(state, empty).unwrap(),
+            Some(deadline) => {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                self.not_empty
+                    .wait_timeout_while(state, timeout, empty)
+                    .unwrap()
+                    .0
+            }
+        }
+    }
+
+    fn send(&self, elem: T, deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
+        let mut state = self.wait_not_full(deadline);
+        if state.receivers == 0 {
+            Err(SendTimeoutError::Disconnected(elem))
+        } else if state.queue.len() >= self.capacity {
+            Err(SendTimeoutError::Timeout(elem))
+        } else {
+            state.queue.push_back(elem);
+            drop(state);
+            self.not_empty.notify_one();
+            Ok(())
+        }
+    }
+
+    fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let mut state = self.wait_not_empty(deadline);
+        match state.queue.pop_front() {
+            Some(elem) => {
+                drop(state);
+                self.not_full.notify_one();
+                Ok(elem)
+            }
+            None if state.senders == 0 => Err(RecvTimeoutError::Disconnected),
+            None => Err(RecvTimeoutError::Timeout),
+        }
+    }
+}
+
+impl<T> Sender<T> {
+    /// Sends a message, blocking while the channel is full.
+    ///
+    /// Fails, giving the message back, if every receiver was dropped.
+    pub fn send(&self, elem: T) -> Result<(), SendError<T>> {
+        self.channel.send(elem, None).map_err(|err| match err {
+            SendTimeoutError::Timeout(_) => unreachable!("no deadline was given"),
+            SendTimeoutError::Disconnected(elem) => SendError(elem),
+        })
+    }
+
+    /// Sends a message, blocking for at most `timeout` while the channel is full.
+    pub fn send_timeout(&self, elem: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        self.channel.send(elem, Some(Instant::now() + timeout))
+    }
+
+    /// Sends a message if the channel has room, without blocking.
+    pub fn try_send(&self, elem: T) -> Result<(), SendTimeoutError<T>> {
+        self.channel.send(elem, Some(Instant::now()))
+    }
+
+    /// Returns the channel shared by this sender.
+    pub fn channel(&self) -> &Channel<T> {
+        &self.channel
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.channel.lock().senders += 1;
+        Self {
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.channel.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            drop(state);
+            // Wake up the receivers so that they see the disconnection.
+            self.channel.not_empty.notify_all();
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Receives a message, blocking while the channel is empty.
+    ///
+    /// Fails if the channel is empty and every sender was dropped.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.channel.recv(None).map_err(|_| RecvError)
+    }
+
+    /// Receives a message, blocking for at most `timeout` while the channel is empty.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.channel.recv(Some(Instant::now() + timeout))
+    }
+
+    /// Receives a message if there is any, without blocking.
+    pub fn try_recv(&self) -> Result<T, RecvTimeoutError> {
+        self.channel.recv(Some(Instant::now()))
+    }
+
+    /// Receives up to `max` messages at once, blocking while the channel is empty.
+    ///
+    /// The messages are split off the channel's queue as a whole, so taking
+    /// every message is O(1), and taking some of them only walks from the
+    /// nearer end of the queue to the split point.
+    pub fn recv_many(&self, max: usize) -> Result<LinkedList<T>, RecvError> {
+        if max == 0 {
+            return Ok(LinkedList::new());
+        }
+
+        let mut state = self.channel.wait_not_empty(None);
+        if state.queue.is_empty() {
+            return Err(RecvError);
+        }
+
+        let len = state.queue.len();
+        let batch = if max >= len {
+            mem::take(&mut state.queue)
+        } else {
+            // Put the cursor at the first message that stays in the queue.
+            let mut cursor = state.queue.cursor_mut();
+            if max < len / 2 {
+                for _ in 0..=max {
+                    cursor.move_next();
+                }
+            } else {
+                for _ in 0..len - max {
+                    cursor.move_prev();
+                }
+            }
+            cursor.split_before()
+        };
+
+        drop(state);
+        self.channel.not_full.notify_all();
+        Ok(batch)
+    }
+
+    /// Returns the channel shared by this receiver.
+    pub fn channel(&self) -> &Channel<T> {
+        &self.channel
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.channel.lock().receivers += 1;
+        Self {
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.channel.lock();
+        state.receivers -= 1;
+        if state.receivers == 0 {
+            drop(state);
+            // Wake up the senders so that they see the disconnection.
+            self.channel.not_full.notify_all();
+        }
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("sending on a disconnected channel")
+    }
+}
+
+impl<T: fmt::Debug> Error for SendError<T> {}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("receiving on an empty and disconnected channel")
+    }
+}
+
+impl Error for RecvError {}
+
+impl<T> fmt::Display for SendTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendTimeoutError::Timeout(_) => f.write_str("timed out sending on a full channel"),
+            SendTimeoutError::Disconnected(_) => f.write_str("sending on a disconnected channel"),
+        }
+    }
+}
+
+impl<T: fmt::Debug> Error for SendTimeoutError<T> {}
+
+impl fmt::Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvTimeoutError::Timeout => f.write_str("timed out receiving on an empty channel"),
+            RecvTimeoutError::Disconnected => {
+                f.write_str("receiving on an empty and disconnected channel")
+            }
+        }
+    }
+}
+
+impl Error for RecvTimeoutError {}
+
+#[cfg(test)]
+mod test {
+    use std::{thread, time::Duration};
+
+    use super::{Channel, RecvError, RecvTimeoutError, SendError, SendTimeoutError};
+
+    #[test]
+    fn basics() {
+        let (tx, rx) = Channel::bounded(4);
+        assert_eq!(tx.channel().capacity(), 4);
+        assert!(rx.channel().is_empty());
+
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        tx.send(3).unwrap();
+        assert_eq!(rx.channel().len(), 3);
+
+        assert_eq!(rx.recv(), Ok(1));
+        assert_eq!(rx.recv(), Ok(2));
+
+        tx.send(4).unwrap();
+
+        assert_eq!(rx.recv(), Ok(3));
+        assert_eq!(rx.recv(), Ok(4));
+        assert_eq!(rx.try_recv(), Err(RecvTimeoutError::Timeout));
+    }
+
+    #[test]
+    fn full() {
+        let (tx, rx) = Channel::bounded(2);
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        assert_eq!(tx.try_send(3), Err(SendTimeoutError::Timeout(3)));
+        assert_eq!(
+            tx.send_timeout(3, Duration::from_millis(10)),
+            Err(SendTimeoutError::Timeout(3))
+        );
+
+        let handle = thread::spawn(move || {
+            // Blocks until the receiver makes room.
+            tx.send(3).unwrap();
+            tx.send(4).unwrap();
+        });
+
+        assert_eq!(rx.recv(), Ok(1));
+        assert_eq!(rx.recv(), Ok(2));
+        assert_eq!(rx.recv(), Ok(3));
+        assert_eq!(rx.recv(), Ok(4));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn timeout() {
+        let (tx, rx) = Channel::bounded(1);
+        assert_eq!(
+            rx.recv_timeout(Duration::from_millis(10)),
+            Err(RecvTimeoutError::Timeout)
+        );
+
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            tx.send(1).unwrap();
+        });
+        assert_eq!(rx.recv_timeout(Duration::from_secs(10)), Ok(1));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn disconnected_senders() {
+        let (tx, rx) = Channel::bounded(4);
+        let tx2 = tx.clone();
+        tx.send(1).unwrap();
+        tx2.send(2).unwrap();
+        drop(tx);
+        drop(tx2);
+
+        // Messages sent before the disconnection are still received.
+        assert_eq!(rx.recv(), Ok(1));
+        assert_eq!(rx.recv(), Ok(2));
+        assert_eq!(rx.recv(), Err(RecvError));
+        assert_eq!(rx.try_recv(), Err(RecvTimeoutError::Disconnected));
+        assert!(rx.recv_many(4).is_err());
+
+        let (tx, rx) = Channel::<i32>::bounded(4);
+        let handle = thread::spawn(move || rx.recv());
+        thread::sleep(Duration::from_millis(10));
+        drop(tx);
+        assert_eq!(handle.join().unwrap(), Err(RecvError));
+    }
+
+    #[test]
+    fn disconnected_receivers() {
+        let (tx, rx) = Channel::bounded(1);
+        let rx2 = rx.clone();
+        drop(rx);
+        tx.send(1).unwrap();
+        drop(rx2);
+        assert_eq!(tx.send(2), Err(SendError(2)));
+        assert_eq!(tx.try_send(3), Err(SendTimeoutError::Disconnected(3)));
+
+        let (tx, rx) = Channel::bounded(1);
+        tx.send(1).unwrap();
+        let handle = thread::spawn(move || tx.send(2));
+        thread::sleep(Duration::from_millis(10));
+        drop(rx);
+        assert_eq!(handle.join().unwrap(), Err(SendError(2)));
+    }
+
+    #[test]
+    fn recv_many() {
+        let (tx, rx) = Channel::bounded(16);
+        for i in 0..10 {
+            tx.send(i).unwrap();
+        }
+
+        assert!(rx.recv_many(0).unwrap().is_empty());
+
+        let batch = rx.recv_many(2).unwrap();
+        assert_eq!(batch.into_iter().collect::<Vec<_>>(), [0, 1]);
+        let batch = rx.recv_many(6).unwrap();
+        assert_eq!(batch.into_iter().collect::<Vec<_>>(), [2, 3, 4, 5, 6, 7]);
+        assert_eq!(rx.channel().len(), 2);
+        let batch = rx.recv_many(100).unwrap();
+        assert_eq!(batch.into_iter().collect::<Vec<_>>(), [8, 9]);
+        assert!(rx.channel().is_empty());
+
+        tx.send(10).unwrap();
+        assert_eq!(rx.recv(), Ok(10));
+    }
+
+    #[test]
+    fn many_senders() {
+        const SENDERS: usize = 4;
+        const PER_SENDER: usize = 1_000;
+
+        let (tx, rx) = Channel::bounded(8);
+        let handles: Vec<_> = (0..SENDERS)
+            .map(|s| {
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    for i in 0..PER_SENDER {
+                        tx.send(s * PER_SENDER + i).unwrap();
+                    }
+                })
+            })
+            .collect();
+        drop(tx);
+
+        let mut received = Vec::new();
+        while let Ok(batch) = rx.recv_many(3) {
+            assert!(batch.len() <= 3);
+            received.extend(batch);
+        }
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        received.sort_unstable();
+        assert_eq!(received, (0..SENDERS * PER_SENDER).collect::<Vec<_>>());
+    }
+}
@@ -1,4 +1,13 @@
-use std::rc::Rc;
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::{BuildHasher, Hash, Hasher, RandomState},
+    ops::Deref,
+    ptr,
+    rc::{Rc, Weak},
+};
+
+use crate::viz::{Dot, Viz};
 
 pub struct List<T> {
     head: Link<T>,
@@ -39,6 +48,15 @@ impl<T> List<T> {
         }
     }
 
+    /// Returns `true` if both lists start at the same node, and therefore
+    /// share all of their nodes.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        }
+    }
+
     /// Returns an iterator over the list.
     pub fn iter(&self) -> Iter<T> {
         Iter {
@@ -47,6 +65,15 @@ impl<T> List<T> {
     }
 }
 
+impl<T> Clone for List<T> {
+    /// Shares the nodes of the list, which is O(1).
+    fn clone(&self) -> Self {
+        List {
+            head: self.head.clone(),
+        }
+    }
+}
+
 impl<T> Drop for List<T> {
     fn drop(&mut self) {
         let mut head = self.head.take();
@@ -60,6 +87,25 @@ impl<T> Drop for List<T> {
     }
 }
 
+impl<T: std::fmt::Debug> Viz for List<T> {
+    fn viz(&self, name: &str, dot: &mut Dot) {
+        dot.entry(name, name);
+        dot.pointer(name, "head", self.head.as_ref().map(Rc::as_ptr));
+
+        let mut link = &self.head;
+        while let Some(node) = link {
+            // The strong count shows how many lists share this node.
+            let count = format!("rc = {}", Rc::strong_count(node));
+            if !dot.node(Rc::as_ptr(node), &node.elem, Some(&count)) {
+                // The rest of the list was drawn as part of another one.
+                break;
+            }
+            dot.edge(Rc::as_ptr(node), node.next.as_ref().map(Rc::as_ptr), "next");
+            link = &node.next;
+        }
+    }
+}
+
 pub struct Iter<'a, T> {
     next: Option<&'a Node<T>>,
 }
@@ -75,9 +121,169 @@ impl<'a, T> Iterator for Iter<'a, T> {
     }
 }
 
+/// Hash-conses lists, so that equal lists built through the same interner are
+/// made of the very same nodes.
+///
+/// The interner only keeps weak references to the nodes it hands out. Nodes
+/// are freed as usual once no list uses them anymore, and their entries are
+/// cleaned up as the interner grows, or by [`Interner::purge`].
+pub struct Interner<T> {
+    // Nodes are looked up by the hash of their element and next pointer.
+    table: HashMap<u64, Vec<Weak<Node<T>>>>,
+    hasher: RandomState,
+    entries: usize,
+    // The number of entries above which dead ones are purged.
+    threshold: usize,
+}
+
+/// A list returned by an [`Interner`].
+///
+/// Since equal interned lists share their nodes, comparing and hashing them
+/// only looks at the address of the first node, which is O(1). This is only
+/// meaningful for lists that come from the same interner.
+pub struct Interned<T> {
+    list: List<T>,
+}
+
+impl<T: Hash + Eq> Interner<T> {
+    /// Creates an empty [`Interner`].
+    pub fn new() -> Self {
+        Interner {
+            table: HashMap::new(),
+            hasher: RandomState::new(),
+            entries: 0,
+            threshold: 16,
+        }
+    }
+
+    /// Returns the number of entries in the interner, including the ones
+    /// whose nodes have been freed but not purged yet.
+    pub fn len(&self) -> usize {
+        self.entries
+    }
+
+    /// Returns `true` if the interner has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries == 0
+    }
+
+    /// Returns the canonical empty list.
+    pub fn empty(&self) -> Interned<T> {
+        Interned { list: List::new() }
+    }
+
+    /// Returns the canonical list made of `elem` followed by `tail`.
+    ///
+    /// If an equal list already exists, its nodes are returned and `elem` is
+    /// dropped.
+    pub fn prepend(&mut self, tail: &Interned<T>, elem: T) -> Interned<T> {
+        let next = tail.list.head.as_ref().map_or(ptr::null(), Rc::as_ptr);
+        let hash = self.hasher.hash_one((&elem, next));
+
+        let bucket = self.table.entry(hash).or_default();
+        for weak in bucket.iter() {
+            if let Some(node) = weak.upgrade() {
+                let node_next = node.next.as_ref().map_or(ptr::null(), Rc::as_ptr);
+                if node.elem == elem && node_next == next {
+                    return Interned {
+                        list: List { head: Some(node) },
+                    };
+                }
+            }
+        }
+
+        let node = Rc::new(Node {
+            elem,
+            next: tail.list.head.clone(),
+        });
+        let before = bucket.len();
+        bucket.retain(|weak| weak.strong_count() > 0);
+        self.entries -= before - bucket.len();
+        bucket.push(Rc::downgrade(&node));
+        self.entries += 1;
+
+        if self.entries > self.threshold {
+            self.purge();
+            self.threshold = (2 * self.entries).max(16);
+        }
+        Interned {
+            list: List { head: Some(node) },
+        }
+    }
+
+    /// Returns the canonical list equal to `list`.
+    pub fn intern(&mut self, list: &List<T>) -> Interned<T>
+    where
+        T: Clone,
+    {
+        let elems: Vec<&T> = list.iter().collect();
+        elems
+            .into_iter()
+            .rev()
+            .fold(self.empty(), |tail, elem| self.prepend(&tail, elem.clone()))
+    }
+
+    /// Removes the entries of the nodes that have been freed.
+    pub fn purge(&mut self) {
+        self.table.retain(|_, bucket| {
+            bucket.retain(|weak| weak.strong_count() > 0);
+            !bucket.is_empty()
+        });
+        self.entries = self.table.values().map(Vec::len).sum();
+    }
+}
+
+impl<T: Hash + Eq> Default for Interner<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Deref for Interned<T> {
+    type Target = List<T>;
+
+    fn deref(&self) -> &List<T> {
+        &self.list
+    }
+}
+
+impl<T> Clone for Interned<T> {
+    fn clone(&self) -> Self {
+        Interned {
+            list: self.list.clone(),
+        }
+    }
+}
+
+impl<T> PartialEq for Interned<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.list.ptr_eq(&other.list)
+    }
+}
+
+impl<T> Eq for Interned<T> {}
+
+impl<T> Hash for Interned<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.list
+            .head
+            .as_ref()
+            .map_or(ptr::null(), Rc::as_ptr)
+            .hash(state);
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Interned<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.list.iter()).finish()
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::List;
+    use std::collections::HashSet;
+
+    use super::{Interner, List};
 
     #[test]
     fn basics() {
@@ -109,4 +315,59 @@ mod test {
         assert_eq!(iter.next(), Some(&2));
         assert_eq!(iter.next(), Some(&1));
     }
+
+    #[test]
+    fn interner() {
+        let mut interner = Interner::new();
+        let empty = interner.empty();
+        let a = interner.prepend(&empty, 1);
+        let a = interner.prepend(&a, 2);
+        let b = interner.intern(&List::new().prepend(1).prepend(2));
+        assert_eq!(a, b);
+        assert!(a.ptr_eq(&b));
+        assert_eq!(format!("{:?}", b), "[2, 1]");
+
+        let c = interner.prepend(&empty, 2);
+        assert_ne!(a, c);
+        assert!(a.tail().ptr_eq(&interner.prepend(&empty, 1)));
+
+        let mut set = HashSet::new();
+        set.insert(a.clone());
+        assert!(set.contains(&b));
+        assert!(!set.contains(&c));
+
+        // A different interner builds different nodes.
+        let other = Interner::new().intern(&a);
+        assert!(!a.ptr_eq(&other));
+        assert!(a.iter().eq(other.iter()));
+    }
+
+    #[test]
+    fn interner_purge() {
+        let mut interner = Interner::new();
+        let keep = interner.intern(&List::new().prepend(1));
+        {
+            let _drop = interner.intern(&List::new().prepend(1).prepend(2).prepend(3));
+            assert_eq!(interner.len(), 3);
+        }
+        interner.purge();
+        assert_eq!(interner.len(), 1);
+        assert!(keep.ptr_eq(&interner.intern(&List::new().prepend(1))));
+
+        // Dead entries dropped from a bucket along the way are not counted.
+        let empty = interner.empty();
+        drop(interner.prepend(&empty, 4));
+        assert_eq!(interner.len(), 2);
+        let four = interner.prepend(&empty, 4);
+        assert_eq!(interner.len(), 2);
+        interner.purge();
+        assert_eq!(interner.len(), 2);
+        drop(four);
+
+        // Dead entries do not pile up.
+        for i in 0..1000 {
+            interner.intern(&List::new().prepend(i));
+        }
+        assert!(interner.len() <= 32);
+    }
 }
@@ -0,0 +1,1198 @@
+//! An unrolled doubly linked list, whose nodes each hold up to [`CAPACITY`]
+//! elements in an inline array.
+//!
+//! Iterating touches one heap node per [`CAPACITY`] elements instead of one per
+//! element, and inserting in the middle only shifts the elements of one node.
+//! A full node is split in half to make room, and removing from the middle
+//! merges a less than half full node with a neighbor when they fit in one.
+
+use core::{
+    fmt::Debug,
+    hash::Hash,
+    marker::PhantomData,
+    mem::{self, MaybeUninit},
+    ptr::{self, NonNull},
+};
+
+use crate::util::drop_all;
+
+/// The maximum number of elements in a node.
+pub const CAPACITY: usize = 16;
+
+const HALF: usize = CAPACITY / 2;
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+/// A node, and the index of an element in it.
+type Position<T> = (NonNull<Node<T>>, usize);
+
+pub struct List<T> {
+    // Nodes in the list are never empty.
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+struct Node<T> {
+    prev: Link<T>,
+    next: Link<T>,
+    // The first `len` elements are initialized.
+    len: usize,
+    elems: [MaybeUninit<T>; CAPACITY],
+}
+
+impl<T> Node<T> {
+    fn alloc() -> NonNull<Self> {
+        let node = Box::new(Node {
+            prev: None,
+            next: None,
+            len: 0,
+            elems: [const { MaybeUninit::uninit() }; CAPACITY],
+        });
+        unsafe { NonNull::new_unchecked(Box::into_raw(node)) }
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == CAPACITY
+    }
+
+    /// Returns a pointer to the element `i`, without borrowing the whole node.
+    ///
+    /// # Safety
+    ///
+    /// `node` must be valid, and `i` must be less than its `len`.
+    unsafe fn elem_ptr(node: NonNull<Self>, i: usize) -> *mut T {
+        ptr::addr_of_mut!((*node.as_ptr()).elems).cast::<T>().add(i)
+    }
+
+    /// Inserts an element at `i`, shifting the following ones.
+    fn insert(&mut self, i: usize, elem: T) {
+        assert!(i <= self.len && !self.is_full());
+        unsafe {
+            let p = self.elems.as_mut_ptr().add(i);
+            ptr::copy(p, p.add(1), self.len - i);
+        }
+        self.elems[i].write(elem);
+        self.len += 1;
+    }
+
+    /// Removes the element at `i`, shifting the following ones.
+    fn remove(&mut self, i: usize) -> T {
+        assert!(i < self.len);
+        unsafe {
+            let p = self.elems.as_mut_ptr().add(i);
+            let elem = p.read().assume_init();
+            ptr::copy(p.add(1), p, self.len - i - 1);
+            self.len -= 1;
+            elem
+        }
+    }
+
+    /// Moves the elements from `at` onwards to the end of `dst`.
+    fn move_tail(&mut self, at: usize, dst: &mut Node<T>) {
+        let count = self.len - at;
+        assert!(dst.len + count <= CAPACITY);
+        unsafe {
+            ptr::copy_nonoverlapping(
+                self.elems.as_ptr().add(at),
+                dst.elems.as_mut_ptr().add(dst.len),
+                count,
+            );
+        }
+        self.len = at;
+        dst.len += count;
+    }
+}
+
+impl<T> Drop for Node<T> {
+    fn drop(&mut self) {
+        // Keeps dropping the rest of the elements if one of them panics.
+        unsafe {
+            let elems =
+                ptr::slice_from_raw_parts_mut(self.elems.as_mut_ptr().cast::<T>(), self.len);
+            ptr::drop_in_place(elems);
+        }
+    }
+}
+
+impl<T> List<T> {
+    /// Creates a new [`List`].
+    pub fn new() -> Self {
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Inserts an element at the beginning of the list.
+    pub fn push_front(&mut self, elem: T) {
+        unsafe {
+            let head = match self.head {
+                Some(head) if !(*head.as_ptr()).is_full() => head,
+                head => {
+                    let new_node = Node::alloc();
+                    self.link_between(new_node, None, head);
+                    new_node
+                }
+            };
+            (*head.as_ptr()).insert(0, elem);
+        }
+        self.len += 1;
+    }
+
+    /// Inserts an element at the back of the list.
+    pub fn push_back(&mut self, elem: T) {
+        unsafe {
+            let tail = match self.tail {
+                Some(tail) if !(*tail.as_ptr()).is_full() => tail,
+                tail => {
+                    let new_node = Node::alloc();
+                    self.link_between(new_node, tail, None);
+                    new_node
+                }
+            };
+            let len = (*tail.as_ptr()).len;
+            (*tail.as_ptr()).insert(len, elem);
+        }
+        self.len += 1;
+    }
+
+    /// Removes an element from the beginning of the list, and returns it.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.map(|head| unsafe { self.remove_at(head, 0) })
+    }
+
+    /// Removes an element from the end of the list, and returns it.
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.tail
+            .map(|tail| unsafe { self.remove_at(tail, (*tail.as_ptr()).len - 1) })
+    }
+
+    /// Returns a reference to the first element of the list.
+    pub fn front(&self) -> Option<&T> {
+        self.head.map(|head| unsafe { &*Node::elem_ptr(head, 0) })
+    }
+
+    /// Returns a mutable reference to the first element of the list.
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.head
+            .map(|head| unsafe { &mut *Node::elem_ptr(head, 0) })
+    }
+
+    /// Returns a reference to the last element of the list.
+    pub fn back(&self) -> Option<&T> {
+        self.tail
+            .map(|tail| unsafe { &*Node::elem_ptr(tail, (*tail.as_ptr()).len - 1) })
+    }
+
+    /// Returns a mutable reference to the last element of the list.
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.tail
+            .map(|tail| unsafe { &mut *Node::elem_ptr(tail, (*tail.as_ptr()).len - 1) })
+    }
+
+    /// The length of the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clears the collection.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Links the nodes from `first` to `last` between `prev` and `next`,
+    /// which must be adjacent. The length of the list is left unchanged.
+    ///
+    /// # Safety
+    ///
+    /// `first` to `last` must be a chain of nodes owned by nothing else, and
+    /// `prev` and `next` must belong to this list.
+    unsafe fn link_chain(
+        &mut self,
+        first: NonNull<Node<T>>,
+        last: NonNull<Node<T>>,
+        prev: Link<T>,
+        next: Link<T>,
+    ) {
+        (*first.as_ptr()).prev = prev;
+        (*last.as_ptr()).next = next;
+        match prev {
+            Some(prev) => (*prev.as_ptr()).next = Some(first),
+            None => self.head = Some(first),
+        }
+        match next {
+            Some(next) => (*next.as_ptr()).prev = Some(last),
+            None => self.tail = Some(last),
+        }
+    }
+
+    /// # Safety
+    ///
+    /// See [`List::link_chain`].
+    unsafe fn link_between(&mut self, node: NonNull<Node<T>>, prev: Link<T>, next: Link<T>) {
+        self.link_chain(node, node, prev, next);
+    }
+
+    /// Unlinks and frees `node`, which must be empty and belong to this list.
+    unsafe fn free_node(&mut self, node: NonNull<Node<T>>) {
+        let boxed_node = Box::from_raw(node.as_ptr());
+        match boxed_node.prev {
+            Some(prev) => (*prev.as_ptr()).next = boxed_node.next,
+            None => self.head = boxed_node.next,
+        }
+        match boxed_node.next {
+            Some(next) => (*next.as_ptr()).prev = boxed_node.prev,
+            None => self.tail = boxed_node.prev,
+        }
+    }
+
+    /// Removes the element `i` of `node`, freeing the node if it becomes empty.
+    unsafe fn remove_at(&mut self, node: NonNull<Node<T>>, i: usize) -> T {
+        let elem = (*node.as_ptr()).remove(i);
+        if (*node.as_ptr()).len == 0 {
+            self.free_node(node);
+        }
+        self.len -= 1;
+        elem
+    }
+
+    /// Splits `node` in two, moving the elements from `at` onwards to a new
+    /// node linked after it, and returns the new node.
+    unsafe fn split_node(&mut self, node: NonNull<Node<T>>, at: usize) -> NonNull<Node<T>> {
+        let new_node = Node::alloc();
+        (*node.as_ptr()).move_tail(at, &mut *new_node.as_ptr());
+        self.link_between(new_node, Some(node), (*node.as_ptr()).next);
+        new_node
+    }
+
+    /// Merges the node after `node` into it, if their elements fit in one node.
+    /// Returns the freed node and the offset its elements were moved to.
+    unsafe fn merge_next(&mut self, node: NonNull<Node<T>>) -> Option<Position<T>> {
+        let next = (*node.as_ptr()).next?;
+        let offset = (*node.as_ptr()).len;
+        if offset + (*next.as_ptr()).len > CAPACITY {
+            return None;
+        }
+        (*next.as_ptr()).move_tail(0, &mut *node.as_ptr());
+        self.free_node(next);
+        Some((next, offset))
+    }
+
+    /// Makes room for one more element in the node at `pos`, splitting it in
+    /// half if it is full, and returns where the element at `pos` is now.
+    unsafe fn make_room(&mut self, (node, i): Position<T>) -> Position<T> {
+        if !(*node.as_ptr()).is_full() {
+            return (node, i);
+        }
+        let new_node = self.split_node(node, HALF);
+        if i < HALF {
+            (node, i)
+        } else {
+            (new_node, i - HALF)
+        }
+    }
+
+    /// Returns the position following `pos`, where `None` is the ghost.
+    fn next_position(&self, pos: Option<Position<T>>) -> Option<Position<T>> {
+        match pos {
+            Some((node, i)) => unsafe {
+                if i + 1 < (*node.as_ptr()).len {
+                    Some((node, i + 1))
+                } else {
+                    (*node.as_ptr()).next.map(|next| (next, 0))
+                }
+            },
+            None => self.head.map(|head| (head, 0)),
+        }
+    }
+
+    /// Returns the position preceding `pos`, where `None` is the ghost.
+    fn prev_position(&self, pos: Option<Position<T>>) -> Option<Position<T>> {
+        let last = |node: NonNull<Node<T>>| unsafe { (node, (*node.as_ptr()).len - 1) };
+        match pos {
+            Some((node, 0)) => unsafe { (*node.as_ptr()).prev.map(last) },
+            Some((node, i)) => Some((node, i - 1)),
+            None => self.tail.map(last),
+        }
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Debug> Debug for List<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: PartialOrd> PartialOrd for List<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for List<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        drop_all(self, |list| {
+            list.head.map(|head| unsafe {
+                list.head = (*head.as_ptr()).next;
+                Box::from_raw(head.as_ptr())
+            })
+        });
+    }
+}
+
+// The list owns its elements, so it is as thread-safe as they are.
+unsafe impl<T: Send> Send for List<T> {}
+unsafe impl<T: Sync> Sync for List<T> {}
+
+pub struct Iter<'a, T> {
+    front: Option<Position<T>>,
+    back: Option<Position<T>>,
+    len: usize,
+    _marker: PhantomData<&'a T>,
+}
+
+impl<T> List<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front: self.next_position(None),
+            back: self.prev_position(None),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Steps a position forward within a chain of nodes, like [`List::next_position`]
+/// but without the list.
+///
+/// # Safety
+///
+/// `node` must be valid, and `i` must be less than its `len`.
+unsafe fn step_next<T>((node, i): Position<T>) -> Option<Position<T>> {
+    if i + 1 < (*node.as_ptr()).len {
+        Some((node, i + 1))
+    } else {
+        (*node.as_ptr()).next.map(|next| (next, 0))
+    }
+}
+
+/// Steps a position backward within a chain of nodes.
+///
+/// # Safety
+///
+/// `node` must be valid, and `i` must be less than its `len`.
+unsafe fn step_prev<T>((node, i): Position<T>) -> Option<Position<T>> {
+    if i > 0 {
+        Some((node, i - 1))
+    } else {
+        (*node.as_ptr())
+            .prev
+            .map(|prev| (prev, (*prev.as_ptr()).len - 1))
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|(node, i)| unsafe {
+                self.len -= 1;
+                self.front = step_next((node, i));
+                &*Node::elem_ptr(node, i)
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|(node, i)| unsafe {
+                self.len -= 1;
+                self.back = step_prev((node, i));
+                &*Node::elem_ptr(node, i)
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+pub struct IterMut<'a, T> {
+    front: Option<Position<T>>,
+    back: Option<Position<T>>,
+    len: usize,
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<T> List<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            front: self.next_position(None),
+            back: self.prev_position(None),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|(node, i)| unsafe {
+                self.len -= 1;
+                self.front = step_next((node, i));
+                &mut *Node::elem_ptr(node, i)
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|(node, i)| unsafe {
+                self.len -= 1;
+                self.back = step_prev((node, i));
+                &mut *Node::elem_ptr(node, i)
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+pub struct IntoIter<T> {
+    list: List<T>,
+}
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+pub struct CursorMut<'a, T> {
+    cur: Option<Position<T>>,
+    list: &'a mut List<T>,
+    index: Option<usize>,
+}
+
+impl<T> List<T> {
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            cur: None,
+            list: self,
+            index: None,
+        }
+    }
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    /// Retreive a current index of the cursor.
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    /// Move the cursor to the next position.
+    pub fn move_next(&mut self) {
+        self.cur = self.list.next_position(self.cur);
+        self.index = match (self.cur, self.index) {
+            (Some(_), Some(index)) => Some(index + 1),
+            (Some(_), None) => Some(0),
+            // We just moved into the ghost element, or the list is empty.
+            (None, _) => None,
+        };
+    }
+
+    /// Move the cursor to the previous position.
+    pub fn move_prev(&mut self) {
+        self.cur = self.list.prev_position(self.cur);
+        self.index = match (self.cur, self.index) {
+            (Some(_), Some(index)) => Some(index - 1),
+            (Some(_), None) => Some(self.list.len - 1),
+            // We just moved into the ghost element, or the list is empty.
+            (None, _) => None,
+        };
+    }
+
+    /// Retrieve an element at the cursor.
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.cur
+            .map(|(node, i)| unsafe { &mut *Node::elem_ptr(node, i) })
+    }
+
+    /// Retrieve the element next to the cursor.
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        self.list
+            .next_position(self.cur)
+            .map(|(node, i)| unsafe { &mut *Node::elem_ptr(node, i) })
+    }
+
+    /// Retrieve the element before the cursor.
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        self.list
+            .prev_position(self.cur)
+            .map(|(node, i)| unsafe { &mut *Node::elem_ptr(node, i) })
+    }
+
+    /// Inserts an element before the cursor.
+    ///
+    /// If the cursor is at the ghost, the element is inserted at the back.
+    pub fn insert_before(&mut self, elem: T) {
+        match self.cur {
+            Some(pos) => unsafe {
+                let (node, i) = self.list.make_room(pos);
+                (*node.as_ptr()).insert(i, elem);
+                self.list.len += 1;
+                self.cur = Some((node, i + 1));
+                self.index = Some(self.index.unwrap() + 1);
+            },
+            None => self.list.push_back(elem),
+        }
+    }
+
+    /// Inserts an element after the cursor.
+    ///
+    /// If the cursor is at the ghost, the element is inserted at the front.
+    pub fn insert_after(&mut self, elem: T) {
+        match self.cur {
+            Some(pos) => unsafe {
+                let (node, i) = self.list.make_room(pos);
+                (*node.as_ptr()).insert(i + 1, elem);
+                self.list.len += 1;
+                self.cur = Some((node, i));
+            },
+            None => self.list.push_front(elem),
+        }
+    }
+
+    /// Creates a new list by splitting the list before the cursor, returning the newly created list.
+    /// The cursor will remain at the original list.
+    pub fn split_before(&mut self) -> List<T> {
+        if let Some((mut node, i)) = self.cur {
+            unsafe {
+                if i > 0 {
+                    node = self.list.split_node(node, i);
+                    self.cur = Some((node, 0));
+                }
+
+                let prev = (*node.as_ptr()).prev;
+                if let Some(prev) = prev {
+                    (*prev.as_ptr()).next = None;
+                    (*node.as_ptr()).prev = None;
+                }
+
+                let splitted_list = List {
+                    head: if prev.is_some() { self.list.head } else { None },
+                    tail: prev,
+                    len: self.index.unwrap(),
+                    _marker: PhantomData,
+                };
+
+                self.list.head = Some(node);
+                self.list.len -= self.index.unwrap();
+                self.index = Some(0);
+
+                splitted_list
+            }
+        } else {
+            // Ghost case, the original list becomes empty.
+            mem::take(self.list)
+        }
+    }
+
+    /// Creates a new list by splitting the list after the cursor, returning the newly created list.
+    /// The cursor will remain at the original list.
+    pub fn split_after(&mut self) -> List<T> {
+        if let Some((node, i)) = self.cur {
+            unsafe {
+                if i + 1 < (*node.as_ptr()).len {
+                    self.list.split_node(node, i + 1);
+                }
+
+                let next = (*node.as_ptr()).next;
+                if let Some(next) = next {
+                    (*next.as_ptr()).prev = None;
+                    (*node.as_ptr()).next = None;
+                }
+
+                let splitted_list = List {
+                    head: next,
+                    tail: if next.is_some() { self.list.tail } else { None },
+                    len: self.list.len - self.index.unwrap() - 1,
+                    _marker: PhantomData,
+                };
+
+                self.list.tail = Some(node);
+                self.list.len = self.index.unwrap() + 1;
+
+                splitted_list
+            }
+        } else {
+            // Ghost case, the original list becomes empty.
+            mem::take(self.list)
+        }
+    }
+
+    /// Inserts the given list before the cursor.
+    pub fn splice_before(&mut self, mut input: List<T>) {
+        let (Some(first), Some(last)) = (input.head.take(), input.tail.take()) else {
+            return;
+        };
+        let len = mem::take(&mut input.len);
+
+        unsafe {
+            if let Some((mut node, i)) = self.cur {
+                if i > 0 {
+                    node = self.list.split_node(node, i);
+                    self.cur = Some((node, 0));
+                }
+                self.list
+                    .link_chain(first, last, (*node.as_ptr()).prev, Some(node));
+                self.index = Some(self.index.unwrap() + len);
+            } else {
+                // Append the input list at the back of current list.
+                // Cursor remains at the ghost.
+                self.list.link_chain(first, last, self.list.tail, None);
+            }
+        }
+        self.list.len += len;
+    }
+
+    /// Inserts the given list after the cursor.
+    pub fn splice_after(&mut self, mut input: List<T>) {
+        let (Some(first), Some(last)) = (input.head.take(), input.tail.take()) else {
+            return;
+        };
+        let len = mem::take(&mut input.len);
+
+        unsafe {
+            if let Some((node, i)) = self.cur {
+                if i + 1 < (*node.as_ptr()).len {
+                    self.list.split_node(node, i + 1);
+                }
+                self.list
+                    .link_chain(first, last, Some(node), (*node.as_ptr()).next);
+            } else {
+                // Append the input list at the start of current list.
+                // Cursor remains at the ghost.
+                self.list.link_chain(first, last, None, self.list.head);
+            }
+        }
+        self.list.len += len;
+    }
+
+    /// Removes the current element and returns it.
+    /// The cursor will be moved to the next element.
+    ///
+    /// If the node of the element becomes less than half full, it is merged
+    /// with a neighbor when their elements fit in one node.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let (node, i) = self.cur?;
+        unsafe {
+            let len = (*node.as_ptr()).len;
+            let elem = (*node.as_ptr()).remove(i);
+            self.list.len -= 1;
+
+            if len == 1 {
+                let next = (*node.as_ptr()).next;
+                self.list.free_node(node);
+                self.cur = next.map(|next| (next, 0));
+            } else {
+                // The following elements of the node shift down by one.
+                self.cur = if i + 1 < len {
+                    Some((node, i))
+                } else {
+                    (*node.as_ptr()).next.map(|next| (next, 0))
+                };
+                if len - 1 < HALF {
+                    self.merge_around(node);
+                }
+            }
+
+            if self.cur.is_none() {
+                self.index = None;
+            }
+            Some(elem)
+        }
+    }
+
+    /// Merges `node` into the previous node, or the next node into `node`,
+    /// if their elements fit in one node, keeping the cursor at its element.
+    unsafe fn merge_around(&mut self, node: NonNull<Node<T>>) {
+        let mut merged = None;
+        if let Some(prev) = (*node.as_ptr()).prev {
+            merged = self
+                .list
+                .merge_next(prev)
+                .map(|(from, offset)| (from, prev, offset));
+        }
+        if merged.is_none() {
+            merged = self
+                .list
+                .merge_next(node)
+                .map(|(from, offset)| (from, node, offset));
+        }
+
+        if let (Some((from, into, offset)), Some((cur, j))) = (merged, self.cur) {
+            if cur == from {
+                self.cur = Some((into, offset + j));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{List, CAPACITY};
+    use crate::util::test::{assert_send_sync, panics, Drops};
+
+    fn list_from<T: Clone>(v: &[T]) -> List<T> {
+        v.iter().map(|x| (*x).clone()).collect()
+    }
+
+    /// Checks that the links are consistent, that no node is empty, and that
+    /// the node lengths add up to the length of the list.
+    fn check_links<T: Eq + std::fmt::Debug>(list: &List<T>) {
+        let from_front: Vec<_> = list.iter().collect();
+        let from_back: Vec<_> = list.iter().rev().collect();
+        let re_reved: Vec<_> = from_back.into_iter().rev().collect();
+        assert_eq!(from_front, re_reved);
+
+        let mut len = 0;
+        let mut prev = None;
+        let mut cur = list.head;
+        while let Some(node) = cur {
+            unsafe {
+                assert_eq!((*node.as_ptr()).prev, prev);
+                assert!((1..=CAPACITY).contains(&(*node.as_ptr()).len));
+                len += (*node.as_ptr()).len;
+                prev = cur;
+                cur = (*node.as_ptr()).next;
+            }
+        }
+        assert_eq!(list.tail, prev);
+        assert_eq!(len, list.len());
+    }
+
+    fn node_count<T>(list: &List<T>) -> usize {
+        let mut count = 0;
+        let mut cur = list.head;
+        while let Some(node) = cur {
+            count += 1;
+            cur = unsafe { (*node.as_ptr()).next };
+        }
+        count
+    }
+
+    #[test]
+    fn basic() {
+        let mut m = List::new();
+        assert_eq!(m.pop_front(), None);
+        assert_eq!(m.pop_back(), None);
+        m.push_front(1);
+        assert_eq!(m.pop_front(), Some(1));
+        m.push_back(2);
+        m.push_back(3);
+        assert_eq!(m.len(), 2);
+        assert_eq!(m.pop_front(), Some(2));
+        assert_eq!(m.pop_front(), Some(3));
+        assert_eq!(m.len(), 0);
+        assert_eq!(m.pop_front(), None);
+
+        let mut n = List::new();
+        n.push_front(2);
+        n.push_front(3);
+        {
+            assert_eq!(n.front().unwrap(), &3);
+            let x = n.front_mut().unwrap();
+            assert_eq!(*x, 3);
+            *x = 0;
+        }
+        {
+            assert_eq!(n.back().unwrap(), &2);
+            let y = n.back_mut().unwrap();
+            assert_eq!(*y, 2);
+            *y = 1;
+        }
+        assert_eq!(n.pop_front(), Some(0));
+        assert_eq!(n.pop_front(), Some(1));
+    }
+
+    #[test]
+    fn many_nodes() {
+        let mut m = List::new();
+        for i in 0..100 {
+            m.push_back(i);
+            m.push_front(-i);
+        }
+        check_links(&m);
+        assert_eq!(m.len(), 200);
+        assert!(node_count(&m) <= 200 / CAPACITY + 2);
+
+        for i in (0..100).rev() {
+            assert_eq!(m.pop_back(), Some(i));
+            assert_eq!(m.pop_front(), Some(-i));
+            check_links(&m);
+        }
+        assert!(m.is_empty());
+        assert_eq!(node_count(&m), 0);
+    }
+
+    #[test]
+    fn iterators() {
+        let mut n: List<i32> = (0..40).collect();
+        let mut it = n.iter();
+        assert_eq!(it.size_hint(), (40, Some(40)));
+        assert_eq!(it.next(), Some(&0));
+        assert_eq!(it.next_back(), Some(&39));
+        assert_eq!(it.len(), 38);
+        assert!(it.copied().eq(1..39));
+
+        let forward: Vec<_> = n.iter().copied().collect();
+        let backward: Vec<_> = n.iter().rev().copied().collect();
+        assert_eq!(forward, (0..40).collect::<Vec<_>>());
+        assert_eq!(backward, (0..40).rev().collect::<Vec<_>>());
+
+        // Meet in the middle of a node.
+        let mut it = n.iter_mut();
+        for _ in 0..20 {
+            *it.next().unwrap() *= 10;
+        }
+        while let Some(elem) = it.next_back() {
+            *elem += 1;
+        }
+        assert_eq!(n.iter().copied().take(3).collect::<Vec<_>>(), [0, 10, 20]);
+        assert_eq!(n.back(), Some(&40));
+
+        let mut it = n.into_iter();
+        assert_eq!(it.next(), Some(0));
+        assert_eq!(it.next_back(), Some(40));
+        assert_eq!(it.len(), 38);
+    }
+
+    #[test]
+    fn traits() {
+        let n: List<i32> = (0..20).collect();
+        let mut m = n.clone();
+        assert_eq!(n, m);
+        assert!(n >= m);
+        m.push_back(20);
+        assert!(n != m);
+        assert!(n < m);
+        assert_eq!(format!("{:?}", list_from(&[0, 1, 2])), "[0, 1, 2]");
+
+        let mut map = std::collections::HashMap::new();
+        map.insert(n.clone(), "n");
+        assert_eq!(map.get(&(0..20).collect()), Some(&"n"));
+    }
+
+    #[test]
+    fn cursor_move_peek() {
+        let mut m: List<u32> = (1..=40).collect();
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.peek_next(), Some(&mut 2));
+        assert_eq!(cursor.peek_prev(), None);
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&mut 1));
+        assert_eq!(cursor.peek_prev(), Some(&mut 40));
+        assert_eq!(cursor.index(), None);
+
+        for mut i in 1..=40 {
+            cursor.move_next();
+            assert_eq!(cursor.current(), Some(&mut i));
+            assert_eq!(cursor.index(), Some(i as usize - 1));
+        }
+        cursor.move_next();
+        assert_eq!(cursor.index(), None);
+        for mut i in (1..=40).rev() {
+            cursor.move_prev();
+            assert_eq!(cursor.current(), Some(&mut i));
+            assert_eq!(cursor.index(), Some(i as usize - 1));
+        }
+    }
+
+    #[test]
+    fn cursor_insert_remove() {
+        let mut m: List<u32> = (0..10).collect();
+        let mut cursor = m.cursor_mut();
+        for _ in 0..5 {
+            cursor.move_next();
+        }
+        // Overflow the node several times.
+        for i in 100..150 {
+            cursor.insert_before(i);
+            cursor.insert_after(i + 100);
+            assert_eq!(cursor.current(), Some(&mut 4));
+        }
+        assert_eq!(cursor.index(), Some(54));
+        check_links(&m);
+        let mut expected: Vec<u32> = (0..4).collect();
+        expected.extend(100..150);
+        expected.push(4);
+        expected.extend((200..250).rev());
+        expected.extend(5..10);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), expected);
+
+        // Remove everything from the middle, merging nodes on the way.
+        let mut cursor = m.cursor_mut();
+        for _ in 0..5 {
+            cursor.move_next();
+        }
+        for i in 100..150 {
+            assert_eq!(cursor.remove_current(), Some(i));
+            assert_eq!(cursor.index(), Some(4));
+        }
+        assert_eq!(cursor.remove_current(), Some(4));
+        for i in (200..250).rev() {
+            assert_eq!(cursor.remove_current(), Some(i));
+        }
+        assert_eq!(cursor.current(), Some(&mut 5));
+        check_links(&m);
+        assert_eq!(
+            m.iter().copied().collect::<Vec<_>>(),
+            [0, 1, 2, 3, 5, 6, 7, 8, 9]
+        );
+        assert!(node_count(&m) <= 2);
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current(), Some(9));
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.current(), None);
+        cursor.insert_before(10);
+        cursor.insert_after(100);
+        check_links(&m);
+        assert_eq!(m.front(), Some(&100));
+        assert_eq!(m.back(), Some(&10));
+    }
+
+    #[test]
+    fn cursor_split_splice() {
+        let mut m: List<u32> = (0..40).collect();
+        let mut cursor = m.cursor_mut();
+        for _ in 0..11 {
+            cursor.move_next();
+        }
+        assert_eq!(cursor.current(), Some(&mut 10));
+        let before = cursor.split_before();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 10));
+        let after = {
+            for _ in 0..20 {
+                cursor.move_next();
+            }
+            assert_eq!(cursor.current(), Some(&mut 30));
+            cursor.split_after()
+        };
+        check_links(&m);
+        check_links(&before);
+        check_links(&after);
+        assert_eq!(
+            before.iter().copied().collect::<Vec<_>>(),
+            (0..10).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            m.iter().copied().collect::<Vec<_>>(),
+            (10..31).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            after.iter().copied().collect::<Vec<_>>(),
+            (31..40).collect::<Vec<_>>()
+        );
+
+        let mut cursor = m.cursor_mut();
+        for _ in 0..6 {
+            cursor.move_next();
+        }
+        assert_eq!(cursor.current(), Some(&mut 15));
+        cursor.splice_after(after);
+        cursor.splice_before(before);
+        assert_eq!(cursor.index(), Some(15));
+        assert_eq!(cursor.current(), Some(&mut 15));
+        cursor.splice_before(List::new());
+        cursor.splice_after(List::new());
+        check_links(&m);
+        let mut expected: Vec<u32> = (10..15).collect();
+        expected.extend(0..10);
+        expected.push(15);
+        expected.extend(31..40);
+        expected.extend(16..31);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), expected);
+        assert_eq!(m.len(), 40);
+
+        // Ghost cases.
+        let mut cursor = m.cursor_mut();
+        cursor.splice_before(list_from(&[100]));
+        cursor.splice_after(list_from(&[200]));
+        assert_eq!(cursor.index(), None);
+        assert_eq!(m.front(), Some(&200));
+        assert_eq!(m.back(), Some(&100));
+        let mut cursor = m.cursor_mut();
+        let all = cursor.split_after();
+        assert!(m.is_empty());
+        assert_eq!(all.len(), 42);
+        check_links(&all);
+    }
+
+    #[test]
+    fn send_sync() {
+        assert_send_sync::<List<i32>>();
+        assert_send_sync::<super::Iter<'_, i32>>();
+        assert_send_sync::<super::IterMut<'_, i32>>();
+    }
+
+    #[test]
+    fn drop_panic() {
+        let drops = Drops::default();
+        let mut list = List::new();
+        for i in 0..3 * CAPACITY {
+            list.push_back(drops.elem(i == 5));
+        }
+
+        assert!(panics(move || drop(list)));
+        assert_eq!(drops.count(), 3 * CAPACITY);
+    }
+}
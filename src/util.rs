@@ -0,0 +1,67 @@
+//! Helpers shared by the lists of this crate.
+
+use core::mem;
+
+/// Drops everything that `pop` takes out of `owner`, one item at a time.
+///
+/// If dropping an item panics, the rest are still dropped during unwinding,
+/// so they are not leaked.
+pub(crate) fn drop_all<O: ?Sized, T>(owner: &mut O, pop: fn(&mut O) -> Option<T>) {
+    struct DropGuard<'a, O: ?Sized, T>(&'a mut O, fn(&mut O) -> Option<T>);
+
+    impl<'a, O: ?Sized, T> Drop for DropGuard<'a, O, T> {
+        fn drop(&mut self) {
+            // Only runs when an item's destructor has panicked.
+            // If another one panics here, the process aborts.
+            while (self.1)(self.0).is_some() {}
+        }
+    }
+
+    while let Some(item) = pop(owner) {
+        let guard = DropGuard(owner, pop);
+        drop(item);
+        mem::forget(guard);
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::{
+        cell::Cell,
+        panic::{catch_unwind, AssertUnwindSafe},
+        rc::Rc,
+    };
+
+    pub(crate) fn assert_send_sync<T: Send + Sync>() {}
+
+    /// Returns `true` if `f` panics.
+    pub(crate) fn panics(f: impl FnOnce()) -> bool {
+        catch_unwind(AssertUnwindSafe(f)).is_err()
+    }
+
+    /// Counts how many of the [`D`]s it made have been dropped.
+    #[derive(Default)]
+    pub(crate) struct Drops(Rc<Cell<usize>>);
+
+    impl Drops {
+        /// Returns a new element, which panics when dropped if `panic` is set.
+        pub(crate) fn elem(&self, panic: bool) -> D {
+            D(self.0.clone(), panic)
+        }
+
+        pub(crate) fn count(&self) -> usize {
+            self.0.get()
+        }
+    }
+
+    pub(crate) struct D(Rc<Cell<usize>>, bool);
+
+    impl Drop for D {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+            if self.1 {
+                panic!("panic in `drop`");
+            }
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! Renders the node structure of the lists in Graphviz DOT format.
+//!
+//! Every node is drawn with its address and element, and every link between
+//! nodes becomes a labelled edge. Nodes are keyed by address, so lists that
+//! share nodes (like two [`third::List`](crate::third::List)s with a common
+//! tail) are drawn sharing them too when added to the same [`Dot`].
+
+use std::{
+    collections::HashSet,
+    fmt::{Debug, Write},
+};
+
+/// A list whose node structure can be rendered to DOT.
+pub trait Viz {
+    /// Writes the nodes and edges reachable from `self` into `dot`, drawing
+    /// its head and tail pointers from an entry node called `name`.
+    fn viz(&self, name: &str, dot: &mut Dot);
+
+    /// Renders `self` alone as a DOT digraph.
+    fn to_dot(&self) -> String {
+        let mut dot = Dot::new();
+        self.viz("list", &mut dot);
+        dot.finish()
+    }
+}
+
+/// A DOT digraph holding one or more lists.
+#[derive(Default)]
+pub struct Dot {
+    body: String,
+    nodes: HashSet<usize>,
+    entries: HashSet<String>,
+    null: bool,
+}
+
+impl Dot {
+    /// Creates an empty [`Dot`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a list to the graph, with its entry node called `name`.
+    pub fn add<V: Viz + ?Sized>(&mut self, name: &str, list: &V) -> &mut Self {
+        list.viz(name, self);
+        self
+    }
+
+    /// Returns the DOT source of the graph.
+    pub fn finish(&self) -> String {
+        format!(
+            "digraph {{\n    node [shape=box, fontname=monospace];\n{}}}\n",
+            self.body
+        )
+    }
+
+    /// Emits the node at `addr`, returning `false` if it was already emitted.
+    pub(crate) fn node<P, T: Debug + ?Sized>(
+        &mut self,
+        addr: *const P,
+        elem: &T,
+        note: Option<&str>,
+    ) -> bool {
+        if !self.nodes.insert(addr as usize) {
+            return false;
+        }
+
+        let mut label = format!("{:p}\\n{}", addr, escape(&format!("{:?}", elem)));
+        if let Some(note) = note {
+            let _ = write!(label, "\\n{}", escape(note));
+        }
+        let _ = writeln!(self.body, "    {} [label=\"{}\"];", id(addr), label);
+        true
+    }
+
+    /// Emits a `label` edge between two nodes, if `to` is not null.
+    pub(crate) fn edge<P>(&mut self, from: *const P, to: Option<*const P>, label: &str) {
+        if let Some(to) = to {
+            let _ = writeln!(
+                self.body,
+                "    {} -> {} [label=\"{}\"];",
+                id(from),
+                id(to),
+                escape(label)
+            );
+        }
+    }
+
+    /// Emits the entry node `name`, showing `label` inside it.
+    pub(crate) fn entry(&mut self, name: &str, label: &str) {
+        if self.entries.insert(name.to_owned()) {
+            let _ = writeln!(
+                self.body,
+                "    \"{}\" [shape=plaintext, label=\"{}\"];",
+                escape(name),
+                escape(label)
+            );
+        }
+    }
+
+    /// Emits a `label` pointer from the entry node `name` to `target`, or to
+    /// a shared null node.
+    pub(crate) fn pointer<P>(&mut self, name: &str, label: &str, target: Option<*const P>) {
+        let to = match target {
+            Some(target) => id(target),
+            None => {
+                if !self.null {
+                    self.null = true;
+                    self.body
+                        .push_str("    null [shape=plaintext, label=\"null\"];\n");
+                }
+                "null".to_owned()
+            }
+        };
+        let _ = writeln!(
+            self.body,
+            "    \"{}\" -> {} [label=\"{}\"];",
+            escape(name),
+            to,
+            escape(label)
+        );
+    }
+
+    /// Emits the ghost element of the cursor `name`, which sits between the
+    /// tail and the head of the list.
+    pub(crate) fn ghost<P>(&mut self, name: &str, head: Option<*const P>, tail: Option<*const P>) {
+        let ghost = escape(&format!("{}.ghost", name));
+        let _ = writeln!(
+            self.body,
+            "    \"{}\" [label=\"ghost\", style=dashed];",
+            ghost
+        );
+        if let Some(tail) = tail {
+            let _ = writeln!(
+                self.body,
+                "    {} -> \"{}\" [label=\"next\", style=dashed];",
+                id(tail),
+                ghost
+            );
+        }
+        if let Some(head) = head {
+            let _ = writeln!(
+                self.body,
+                "    \"{}\" -> {} [label=\"next\", style=dashed];",
+                ghost,
+                id(head)
+            );
+        }
+    }
+
+    /// Emits the position of the cursor `name`, highlighting the current node
+    /// or, if `cur` is null, its ghost element.
+    pub(crate) fn cursor<P>(&mut self, name: &str, cur: Option<*const P>, index: Option<usize>) {
+        let label = match index {
+            Some(index) => format!("cur (index = {})", index),
+            None => "cur (ghost)".to_owned(),
+        };
+        let target = match cur {
+            Some(cur) => id(cur),
+            None => format!("\"{}\"", escape(&format!("{}.ghost", name))),
+        };
+        let _ = writeln!(
+            self.body,
+            "    {} [style=\"filled,{}\", fillcolor=lightblue];",
+            target,
+            if cur.is_some() { "solid" } else { "dashed" }
+        );
+        let _ = writeln!(
+            self.body,
+            "    \"{}\" -> {} [label=\"{}\", color=blue];",
+            escape(name),
+            target,
+            escape(&label)
+        );
+    }
+}
+
+/// The DOT identifier of the node at `addr`.
+fn id<P>(addr: *const P) -> String {
+    format!("n{:x}", addr as usize)
+}
+
+/// Escapes `s` for use inside a quoted DOT string.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Dot, Viz};
+
+    fn count(dot: &str, pat: &str) -> usize {
+        dot.matches(pat).count()
+    }
+
+    #[test]
+    fn stacks() {
+        let mut first = crate::first::List::new();
+        let mut second = crate::second::List::new();
+        for i in 0..3 {
+            first.push(i);
+            second.push(i);
+        }
+
+        for dot in [first.to_dot(), second.to_dot()] {
+            assert!(dot.starts_with("digraph {\n"));
+            assert!(dot.ends_with("}\n"));
+            assert_eq!(count(&dot, "[label=\"head\"]"), 1);
+            assert_eq!(count(&dot, "[label=\"next\"]"), 2);
+            assert_eq!(count(&dot, "\\n0\""), 1);
+            assert_eq!(count(&dot, "\\n2\""), 1);
+        }
+
+        let dot = crate::second::List::<i32>::new().to_dot();
+        assert!(dot.contains("\"list\" -> null [label=\"head\"];"));
+    }
+
+    #[test]
+    fn shared_tails() {
+        let base = crate::third::List::new().prepend(1).prepend(2);
+        let a = base.prepend(3);
+        let b = base.prepend(4);
+
+        let dot = Dot::new().add("a", &a).add("b", &b).finish();
+        // Both lists point into the same two shared nodes.
+        assert_eq!(count(&dot, "[label=\"0x"), 4);
+        assert_eq!(count(&dot, "[label=\"next\"]"), 3);
+        assert_eq!(count(&dot, "\\nrc = 3\""), 1);
+        assert_eq!(count(&dot, "\\nrc = 1\""), 3);
+    }
+
+    #[test]
+    fn queues() {
+        let mut fourth = crate::fourth::List::new();
+        let mut fifth = crate::fifth::List::new();
+        let mut linked: crate::linked_list::LinkedList<&str> = Default::default();
+        for s in ["a", "b\"c", "d"] {
+            fourth.push_back(s);
+            fifth.push_back(s);
+            linked.push_back(s);
+        }
+
+        for dot in [fourth.to_dot(), linked.to_dot()] {
+            assert_eq!(count(&dot, "[label=\"next\"]"), 2);
+            assert_eq!(count(&dot, "[label=\"prev\"]"), 2);
+            assert_eq!(count(&dot, "[label=\"head\"]"), 1);
+            assert_eq!(count(&dot, "[label=\"tail\"]"), 1);
+            assert!(dot.contains("\\\"b\\\\\\\"c\\\""));
+        }
+
+        let dot = fifth.to_dot();
+        assert_eq!(count(&dot, "[label=\"next\"]"), 2);
+        assert_eq!(count(&dot, "[label=\"prev\"]"), 0);
+        assert!(linked.to_dot().contains("len = 3"));
+    }
+
+    #[test]
+    fn cursor() {
+        let mut list: crate::linked_list::LinkedList<i32> = (0..3).collect();
+
+        let cursor = list.cursor_mut();
+        let dot = cursor.to_dot();
+        assert!(dot.contains("\"list\" -> \"list.ghost\" [label=\"cur (ghost)\", color=blue];"));
+        assert_eq!(count(&dot, "style=dashed];"), 3);
+
+        let mut cursor = list.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        let dot = cursor.to_dot();
+        assert!(dot.contains("[label=\"cur (index = 1)\", color=blue];"));
+        assert_eq!(count(&dot, "fillcolor=lightblue"), 1);
+    }
+}
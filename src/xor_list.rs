@@ -0,0 +1,563 @@
+//! A doubly linked list storing a single `prev ^ next` address per node,
+//! instead of two pointers.
+//!
+//! Walking the list needs the address of the node it came from, so only
+//! the ends can be reached directly, but which end is the front is just a
+//! matter of which one we start from: [`List::reverse`] is O(1).
+
+use core::{fmt::Debug, hash::Hash, marker::PhantomData, mem, ptr::NonNull};
+
+use crate::util::drop_all;
+
+pub struct List<T> {
+    head: Option<NonNull<Node<T>>>,
+    tail: Option<NonNull<Node<T>>>,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+struct Node<T> {
+    // The addresses of the previous and the next node xored, where a missing
+    // node is 0.
+    link: usize,
+    elem: T,
+}
+
+/// The address of a node, exposing its provenance so it can be recovered
+/// from a xored link.
+fn addr<T>(node: Option<NonNull<Node<T>>>) -> usize {
+    node.map_or(0, |node| node.as_ptr().expose_provenance())
+}
+
+/// Recovers a node from an address computed from xored links.
+fn from_addr<T>(addr: usize) -> Option<NonNull<Node<T>>> {
+    NonNull::new(core::ptr::with_exposed_provenance_mut(addr))
+}
+
+impl<T> List<T> {
+    /// Creates a new [`List`].
+    pub fn new() -> Self {
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Inserts an element at the beginning of the list.
+    pub fn push_front(&mut self, elem: T) {
+        let (head, tail) = (self.head, self.tail);
+        let new_node = unsafe { Self::push_end(head, elem) };
+        self.head = Some(new_node);
+        if tail.is_none() {
+            self.tail = Some(new_node);
+        }
+        self.len += 1;
+    }
+
+    /// Inserts an element at the back of the list.
+    pub fn push_back(&mut self, elem: T) {
+        let (head, tail) = (self.head, self.tail);
+        let new_node = unsafe { Self::push_end(tail, elem) };
+        self.tail = Some(new_node);
+        if head.is_none() {
+            self.head = Some(new_node);
+        }
+        self.len += 1;
+    }
+
+    /// Removes an element from the beginning of the list, and returns it.
+    pub fn pop_front(&mut self) -> Option<T> {
+        let head = self.head?;
+        let new_head = unsafe { Self::pop_end(head) };
+        self.head = new_head;
+        if new_head.is_none() {
+            self.tail = None;
+        }
+        self.len -= 1;
+        Some(unsafe { Box::from_raw(head.as_ptr()).elem })
+    }
+
+    /// Removes an element from the end of the list, and returns it.
+    pub fn pop_back(&mut self) -> Option<T> {
+        let tail = self.tail?;
+        let new_tail = unsafe { Self::pop_end(tail) };
+        self.tail = new_tail;
+        if new_tail.is_none() {
+            self.head = None;
+        }
+        self.len -= 1;
+        Some(unsafe { Box::from_raw(tail.as_ptr()).elem })
+    }
+
+    /// Returns a reference to the first element of the list.
+    pub fn front(&self) -> Option<&T> {
+        self.head.map(|node| unsafe { &(*node.as_ptr()).elem })
+    }
+
+    /// Returns a mutable reference to the first element of the list.
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.head.map(|node| unsafe { &mut (*node.as_ptr()).elem })
+    }
+
+    /// Returns a reference to the last element of the list.
+    pub fn back(&self) -> Option<&T> {
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).elem })
+    }
+
+    /// Returns a mutable reference to the last element of the list.
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.tail.map(|node| unsafe { &mut (*node.as_ptr()).elem })
+    }
+
+    /// The length of the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clears the collection.
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    /// Reverses the order of the elements in O(1).
+    pub fn reverse(&mut self) {
+        mem::swap(&mut self.head, &mut self.tail);
+    }
+
+    /// Allocates a node holding `elem` beyond `end`, the head or the tail,
+    /// and links them.
+    unsafe fn push_end(end: Option<NonNull<Node<T>>>, elem: T) -> NonNull<Node<T>> {
+        let new_node = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+            // Nothing lies beyond the new node.
+            link: addr(end),
+            elem,
+        })));
+        if let Some(end) = end {
+            // `end` had nothing beyond it, and now has the new node.
+            (*end.as_ptr()).link ^= addr(Some(new_node));
+        }
+        new_node
+    }
+
+    /// Unlinks `end`, the head or the tail, from its neighbor, and returns the
+    /// neighbor. The node itself is left for the caller to free.
+    unsafe fn pop_end(end: NonNull<Node<T>>) -> Option<NonNull<Node<T>>> {
+        // Nothing lies beyond `end`, so its link is the address of its neighbor.
+        let neighbor = from_addr((*end.as_ptr()).link);
+        if let Some(neighbor) = neighbor {
+            (*neighbor.as_ptr()).link ^= addr(Some(end));
+        }
+        neighbor
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Debug> Debug for List<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: PartialOrd> PartialOrd for List<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for List<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        drop_all(self, Self::pop_front);
+    }
+}
+
+// The list owns its elements, so it is as thread-safe as they are.
+unsafe impl<T: Send> Send for List<T> {}
+unsafe impl<T: Sync> Sync for List<T> {}
+
+/// One end of an iteration: the next node to yield, and the address of the
+/// node yielded before it from the same end.
+struct Walker<T> {
+    cur: Option<NonNull<Node<T>>>,
+    came_from: usize,
+}
+
+impl<T> Walker<T> {
+    fn new(end: Option<NonNull<Node<T>>>) -> Self {
+        Self {
+            cur: end,
+            came_from: 0,
+        }
+    }
+
+    /// Steps to the next node, and returns the current one.
+    unsafe fn step(&mut self) -> Option<NonNull<Node<T>>> {
+        let node = self.cur?;
+        self.cur = from_addr((*node.as_ptr()).link ^ self.came_from);
+        self.came_from = addr(Some(node));
+        Some(node)
+    }
+}
+
+pub struct Iter<'a, T> {
+    front: Walker<T>,
+    back: Walker<T>,
+    len: usize,
+    _marker: PhantomData<&'a T>,
+}
+
+impl<T> List<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front: Walker::new(self.head),
+            back: Walker::new(self.tail),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.len -= 1;
+            unsafe { self.front.step().map(|node| &(*node.as_ptr()).elem) }
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.len -= 1;
+            unsafe { self.back.step().map(|node| &(*node.as_ptr()).elem) }
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+pub struct IterMut<'a, T> {
+    front: Walker<T>,
+    back: Walker<T>,
+    len: usize,
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<T> List<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            front: Walker::new(self.head),
+            back: Walker::new(self.tail),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.len -= 1;
+            unsafe { self.front.step().map(|node| &mut (*node.as_ptr()).elem) }
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.len -= 1;
+            unsafe { self.back.step().map(|node| &mut (*node.as_ptr()).elem) }
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+pub struct IntoIter<T> {
+    list: List<T>,
+}
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+#[cfg(test)]
+mod test {
+    use std::mem::size_of;
+
+    use super::List;
+    use crate::util::test::{assert_send_sync, panics, Drops};
+
+    fn check_links<T: Eq + std::fmt::Debug>(list: &List<T>) {
+        let from_front: Vec<_> = list.iter().collect();
+        let from_back: Vec<_> = list.iter().rev().collect();
+        let re_reved: Vec<_> = from_back.into_iter().rev().collect();
+
+        assert_eq!(from_front, re_reved);
+        assert_eq!(from_front.len(), list.len());
+    }
+
+    #[test]
+    fn basics() {
+        let mut list = List::new();
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+
+        list.push_back(2);
+        list.push_front(1);
+        list.push_back(3);
+        check_links(&list);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.front(), Some(&1));
+        assert_eq!(list.back(), Some(&3));
+        *list.front_mut().unwrap() = 10;
+        *list.back_mut().unwrap() = 30;
+
+        assert_eq!(list.pop_front(), Some(10));
+        check_links(&list);
+        assert_eq!(list.pop_back(), Some(30));
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(list.pop_front(), None);
+        assert!(list.is_empty());
+
+        list.push_front(4);
+        assert_eq!(list.pop_back(), Some(4));
+        assert_eq!((list.front(), list.back()), (None, None));
+    }
+
+    #[test]
+    fn iterators() {
+        let mut list: List<i32> = (0..10).collect();
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&0));
+        assert_eq!(iter.next_back(), Some(&9));
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&8));
+        assert_eq!(iter.len(), 6);
+        assert!(iter.eq(&[2, 3, 4, 5, 6, 7]));
+
+        for elem in list.iter_mut().rev().take(5) {
+            *elem *= 10;
+        }
+        assert!(list.iter().eq(&[0, 1, 2, 3, 4, 50, 60, 70, 80, 90]));
+
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next_back(), Some(90));
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.len(), 8);
+    }
+
+    #[test]
+    fn reverse() {
+        let mut list: List<i32> = (0..5).collect();
+        list.reverse();
+        check_links(&list);
+        assert!(list.iter().eq(&[4, 3, 2, 1, 0]));
+
+        // Both ends keep working after reversing.
+        list.push_front(5);
+        list.push_back(-1);
+        assert_eq!(list.pop_back(), Some(-1));
+        list.reverse();
+        check_links(&list);
+        assert!(list.iter().eq(&[0, 1, 2, 3, 4, 5]));
+
+        let mut empty: List<i32> = List::new();
+        empty.reverse();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn traits() {
+        let list: List<i32> = (0..5).collect();
+        let mut other = list.clone();
+        assert_eq!(list, other);
+        other.push_back(5);
+        assert!(list != other);
+        assert!(list < other);
+        assert_eq!(format!("{:?}", list), "[0, 1, 2, 3, 4]");
+
+        let mut map = std::collections::HashMap::new();
+        map.insert(list.clone(), "list");
+        assert_eq!(map.get(&list), Some(&"list"));
+    }
+
+    #[test]
+    fn footprint() {
+        use crate::linked_list;
+
+        // A node saves one pointer over `LinkedList`.
+        assert_eq!(
+            size_of::<super::Node<u64>>() + size_of::<usize>(),
+            size_of::<linked_list::Node<u64>>()
+        );
+        assert_eq!(
+            size_of::<super::Node<u8>>() + size_of::<usize>(),
+            size_of::<linked_list::Node<u8>>()
+        );
+        assert_eq!(size_of::<super::Node<u64>>(), 2 * size_of::<usize>());
+        // The list itself is the same size.
+        assert_eq!(
+            size_of::<List<u64>>(),
+            size_of::<linked_list::LinkedList<u64>>()
+        );
+    }
+
+    #[test]
+    fn send_sync() {
+        assert_send_sync::<List<i32>>();
+        assert_send_sync::<super::Iter<'_, i32>>();
+        assert_send_sync::<super::IterMut<'_, i32>>();
+    }
+
+    #[test]
+    fn drop_panic() {
+        let drops = Drops::default();
+        let mut list = List::new();
+        list.push_back(drops.elem(false));
+        list.push_back(drops.elem(true));
+        list.push_back(drops.elem(false));
+        list.push_front(drops.elem(false));
+
+        assert!(panics(move || drop(list)));
+        assert_eq!(drops.count(), 4);
+    }
+}